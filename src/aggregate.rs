@@ -0,0 +1,378 @@
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::{
+    LocalDateTime, RainInterval, TimeZone, Timestamp, UtcOffset, WeatherData, WindDirection,
+};
+
+/// The fields that hold timestamps rather than readings, and so are not aggregated.
+const TIME_FIELDS: [&str; 2] = ["dateutc", "lightning_time"];
+
+/// The fields that count up over time rather than measure, and so are not aggregated. Rain is aggregated from the rise of its counters instead, see [`Aggregate::rain`].
+const COUNTER_FIELDS: [&str; 8] = [
+    "eventrainin",
+    "dailyrainin",
+    "weeklyrainin",
+    "monthlyrainin",
+    "yearlyrainin",
+    "totalrainin",
+    "lightning_day",
+    "lightning_hour",
+];
+
+/// The prefixes of the fields that report the state of a battery, leak detector or relay rather than a reading, and so are not aggregated.
+const STATE_PREFIXES: [&str; 4] = ["batt", "batleak", "leak", "relay"];
+
+/// The length of the time windows [`Aggregate::from_records`] groups records into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Period {
+    /// One clock hour.
+    Hour,
+    /// One calendar day, from midnight to midnight.
+    Day,
+    /// One calendar month.
+    Month,
+}
+
+/// The minimum, maximum, mean and sum of one field over the records of an [`Aggregate`], along with when the extremes were reached.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldStatistics {
+    /// The lowest value.
+    pub min: f32,
+    /// When the lowest value was first reported.
+    pub min_time: Timestamp,
+    /// The highest value.
+    pub max: f32,
+    /// When the highest value was first reported.
+    pub max_time: Timestamp,
+    /// The mean of every value.
+    pub mean: f32,
+    /// The sum of every value.
+    pub sum: f32,
+    /// The number of records that reported the field.
+    pub count: usize,
+}
+
+/// The readings of every record in one hour, day or month, summarized field by field.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{Aggregate, Period, WeatherData};
+///
+/// // Just before and just after midnight in New York, and the following afternoon
+/// let records: Vec<WeatherData> = [
+///     ("2023-10-15T03:55:00.000Z", 52.0),
+///     ("2023-10-15T04:05:00.000Z", 49.5),
+///     ("2023-10-15T18:00:00.000Z", 68.0),
+/// ]
+/// .into_iter()
+/// .map(|(date, tempf)| serde_json::from_value(serde_json::json!({
+///     "dateutc": date,
+///     "tempf": tempf,
+///     "tz": "America/New_York",
+/// })).unwrap())
+/// .collect();
+///
+/// let days = Aggregate::from_records(&records, Period::Day);
+/// assert_eq!(days.len(), 2);
+/// assert_eq!(days[1].start.to_string(), "2023-10-15T00:00:00.000-04:00");
+///
+/// let temperature = days[1].field("tempf").unwrap();
+/// assert_eq!((temperature.min, temperature.max, temperature.count), (49.5, 68.0, 2));
+/// assert_eq!(temperature.max_time.to_string(), "2023-10-15T18:00:00.000Z");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Aggregate {
+    /// The length of the window.
+    pub period: Period,
+    /// The start of the window, in the time zone of its records.
+    pub start: LocalDateTime,
+    /// The number of records in the window.
+    pub records: usize,
+    /// The statistics of every numeric measurement reported in the window, by its Ambient Weather name, such as `tempf` or `temp1f`. Wind directions, battery, leak and relay states, and cumulative counters such as `dailyrainin` are left out.
+    pub fields: BTreeMap<String, FieldStatistics>,
+    /// The mean of every wind direction reported in the window, such as `winddir` or `windgustdir`, by its Ambient Weather name, averaged around the compass with [`WindDirection::mean`]. Directions that cancel each other out are left out.
+    pub wind_directions: BTreeMap<String, WindDirection>,
+    /// The rain that fell in the window, in inches, from the rise of the rain counters, see [`RainInterval::from_records`]. The rain between two records counts towards the window of the later one. `None` if no record in the window reported a rain counter.
+    pub rain: Option<f32>,
+}
+
+impl Aggregate {
+    /// Groups a sequence of records, such as those returned by [`AmbientWeatherClient::historic`](crate::AmbientWeatherClient::historic), into hourly, daily or monthly windows, and summarizes the measurements, wind directions and rain of each. The windows are returned oldest first.
+    ///
+    /// Windows follow the wall clock of each record's time zone, `tz`, so a day runs from local midnight to local midnight. Records without a known time zone are grouped in UTC, and records without a `dateutc` are skipped.
+    pub fn from_records(records: &[WeatherData], period: Period) -> Vec<Aggregate> {
+        // Sums are kept at full precision next to each field until every record has been added, as are the directions to average.
+        let mut windows: BTreeMap<_, (Aggregate, BTreeMap<String, f64>, Directions)> =
+            BTreeMap::new();
+        let mut window_of = HashMap::new();
+
+        for (time, local_time, data) in local_records(records) {
+            let start = window_start(local_time, period, data.tz.as_ref());
+            // Clock hours repeat when daylight saving time ends, so hours are told apart by their offset.
+            let offset = match period {
+                Period::Hour => start.offset.seconds(),
+                Period::Day | Period::Month => 0,
+            };
+            let key = (start.year, start.month, start.day, start.hour, offset);
+            window_of.insert(time, key);
+
+            let (window, sums, directions) = windows.entry(key).or_insert_with(|| {
+                let window = Aggregate {
+                    period,
+                    start,
+                    records: 0,
+                    fields: BTreeMap::new(),
+                    wind_directions: BTreeMap::new(),
+                    rain: None,
+                };
+                (window, BTreeMap::new(), BTreeMap::new())
+            });
+            window.records += 1;
+
+            let (measurements, wind_directions) = readings(data);
+            for (field, direction) in wind_directions {
+                directions
+                    .entry(field)
+                    .or_default()
+                    .push(WindDirection::from_degrees(direction));
+            }
+            if data.totalrainin.is_some() || data.dailyrainin.is_some() {
+                window.rain.get_or_insert(0.0);
+            }
+
+            for (field, value) in measurements {
+                *sums.entry(field.clone()).or_default() += f64::from(value);
+
+                let statistics = window.fields.entry(field).or_insert(FieldStatistics {
+                    min: value,
+                    min_time: time,
+                    max: value,
+                    max_time: time,
+                    mean: value,
+                    sum: 0.0,
+                    count: 0,
+                });
+                if value < statistics.min {
+                    statistics.min = value;
+                    statistics.min_time = time;
+                }
+                if value > statistics.max {
+                    statistics.max = value;
+                    statistics.max_time = time;
+                }
+                statistics.count += 1;
+            }
+        }
+
+        for interval in RainInterval::from_records(records) {
+            if let Some((window, ..)) = window_of
+                .get(&interval.end)
+                .and_then(|key| windows.get_mut(key))
+            {
+                *window.rain.get_or_insert(0.0) += interval.rain.inches();
+            }
+        }
+
+        windows
+            .into_values()
+            .map(|(mut window, sums, directions)| {
+                for (field, statistics) in &mut window.fields {
+                    let sum = sums[field];
+                    statistics.sum = sum as f32;
+                    statistics.mean = (sum / statistics.count as f64) as f32;
+                }
+                window.wind_directions = directions
+                    .into_iter()
+                    .filter_map(|(field, directions)| {
+                        Some((field, WindDirection::mean(directions)?))
+                    })
+                    .collect();
+                window
+            })
+            .collect()
+    }
+
+    /// Returns the statistics of a field by its Ambient Weather name, such as `tempf`, or `None` if no record in the window reported it.
+    pub fn field(&self, name: &str) -> Option<&FieldStatistics> {
+        self.fields.get(name)
+    }
+}
+
+/// Returns the records that have a `dateutc`, oldest first, along with their time and the wall clock time in their time zone, or in UTC for records without a known time zone.
+pub(crate) fn local_records(
+    records: &[WeatherData],
+) -> Vec<(Timestamp, LocalDateTime, &WeatherData)> {
+    let mut records: Vec<(Timestamp, LocalDateTime, &WeatherData)> = records
+        .iter()
+        .filter_map(|data| {
+            let time = data.dateutc?;
+            let local_time = data.local_time().unwrap_or_else(|| time.to_utc());
+            Some((time, local_time, data))
+        })
+        .collect();
+    records.sort_by_key(|&(time, ..)| time);
+
+    records
+}
+
+/// The wind directions of a window, by field, until they are averaged.
+type Directions = BTreeMap<String, Vec<WindDirection>>;
+
+/// The readings of a record, by field.
+type Readings = Vec<(String, f32)>;
+
+/// A private function that returns the start of the window a local time falls in, in the given time zone.
+pub(crate) fn window_start(
+    local_time: LocalDateTime,
+    period: Period,
+    time_zone: Option<&TimeZone>,
+) -> LocalDateTime {
+    let start = LocalDateTime {
+        day: match period {
+            Period::Month => 1,
+            Period::Hour | Period::Day => local_time.day,
+        },
+        hour: match period {
+            Period::Hour => local_time.hour,
+            Period::Day | Period::Month => 0,
+        },
+        minute: 0,
+        second: 0,
+        millisecond: 0,
+        ..local_time
+    };
+
+    // A day or month can start on the other side of a daylight saving time change from the record, so the offset at its start is looked up on its own. Hours are never split by a change.
+    match (period, time_zone) {
+        (Period::Day | Period::Month, Some(time_zone)) => {
+            local_offset(start, time_zone).map_or(start, |offset| LocalDateTime { offset, ..start })
+        }
+        _ => start,
+    }
+}
+
+/// A private function that returns the offset a time zone is at on a wall clock time, going by the offset of the time zone at the moment it refers to. Returns `None` if the rules of the time zone are unknown.
+pub(crate) fn local_offset(local_time: LocalDateTime, time_zone: &TimeZone) -> Option<UtcOffset> {
+    let guess = time_zone.offset_at(local_time.timestamp())?;
+
+    time_zone.offset_at(
+        LocalDateTime {
+            offset: guess,
+            ..local_time
+        }
+        .timestamp(),
+    )
+}
+
+/// A private function that returns the measurements and wind directions of a record by their Ambient Weather names, including the numbered channels and fields this crate does not model yet.
+fn readings(data: &WeatherData) -> (Readings, Readings) {
+    let Ok(Value::Object(fields)) = serde_json::to_value(data) else {
+        return (Vec::new(), Vec::new());
+    };
+
+    let (wind_directions, measurements) = fields
+        .into_iter()
+        .filter(|(field, _)| {
+            !TIME_FIELDS.contains(&field.as_str())
+                && !COUNTER_FIELDS.contains(&field.as_str())
+                && !STATE_PREFIXES
+                    .iter()
+                    .any(|prefix| field.starts_with(prefix))
+        })
+        .filter_map(|(field, value)| Some((field, value.as_f64()? as f32)))
+        .partition(|(field, _)| field.starts_with("winddir") || field == "windgustdir");
+
+    (measurements, wind_directions)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(readings: &[serde_json::Value]) -> Vec<WeatherData> {
+        readings
+            .iter()
+            .map(|reading| serde_json::from_value(reading.clone()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn aggregates_only_measurements() {
+        let records = records(&[serde_json::json!({
+            "dateutc": "2023-10-15T12:00:00.000Z",
+            "tempf": 60.0,
+            "battout": 1,
+            "batt1": 0,
+            "batleak1": 0,
+            "leak1": 1,
+            "relay1": 1,
+            "dailyrainin": 0.2,
+            "totalrainin": 12.5,
+            "lightning_day": 4,
+            "winddir": 90,
+        })]);
+
+        let hours = Aggregate::from_records(&records, Period::Hour);
+        let fields: Vec<&str> = hours[0].fields.keys().map(String::as_str).collect();
+
+        assert_eq!(fields, ["tempf"]);
+    }
+
+    #[test]
+    fn averages_wind_directions_around_the_compass() {
+        let records = records(&[
+            serde_json::json!({ "dateutc": "2023-10-15T12:00:00.000Z", "winddir": 350, "windgustdir": 0, "winddir_avg10m": 340 }),
+            serde_json::json!({ "dateutc": "2023-10-15T12:05:00.000Z", "winddir": 10, "windgustdir": 180, "winddir_avg10m": 20 }),
+        ]);
+
+        let hour = &Aggregate::from_records(&records, Period::Hour)[0];
+
+        assert_eq!(hour.wind_directions["winddir"].compass_point(), "N");
+        assert_eq!(hour.wind_directions["winddir_avg10m"].compass_point(), "N");
+        assert!(!hour.wind_directions.contains_key("windgustdir"));
+        assert!(hour.fields.is_empty());
+    }
+
+    #[test]
+    fn totals_rain_from_counter_increases() {
+        // `dailyrainin` resets at midnight UTC, between the second and third record
+        let records = records(&[
+            serde_json::json!({ "dateutc": "2023-10-15T23:00:00.000Z", "dailyrainin": 0.30 }),
+            serde_json::json!({ "dateutc": "2023-10-15T23:30:00.000Z", "dailyrainin": 0.35 }),
+            serde_json::json!({ "dateutc": "2023-10-16T00:00:00.000Z", "dailyrainin": 0.02 }),
+            serde_json::json!({ "dateutc": "2023-10-16T01:00:00.000Z", "dailyrainin": 0.10 }),
+            serde_json::json!({ "dateutc": "2023-10-16T02:00:00.000Z", "tempf": 50.0 }),
+        ]);
+
+        let days = Aggregate::from_records(&records, Period::Day);
+        let rain: Vec<Option<f32>> = days
+            .iter()
+            .map(|day| day.rain.map(|rain| (rain * 100.0).round()))
+            .collect();
+
+        assert_eq!(rain, [Some(5.0), Some(10.0)]);
+        assert!(days.iter().all(|day| day.field("dailyrainin").is_none()));
+
+        let hours = Aggregate::from_records(&records, Period::Hour);
+        assert_eq!(hours.last().unwrap().rain, None);
+    }
+
+    #[test]
+    fn starts_windows_at_their_own_offset_across_daylight_saving_time() {
+        // Daylight saving time ended in New York on 2023-11-05, after the month and that day began
+        let records = records(&[
+            serde_json::json!({ "dateutc": "2023-11-05T12:00:00.000Z", "tempf": 50.0, "tz": "America/New_York" }),
+            serde_json::json!({ "dateutc": "2023-11-20T12:00:00.000Z", "tempf": 40.0, "tz": "America/New_York" }),
+        ]);
+
+        let months = Aggregate::from_records(&records, Period::Month);
+        assert_eq!(months.len(), 1);
+        assert_eq!(months[0].start.to_string(), "2023-11-01T00:00:00.000-04:00");
+
+        let days = Aggregate::from_records(&records, Period::Day);
+        assert_eq!(days[0].start.to_string(), "2023-11-05T00:00:00.000-04:00");
+        assert_eq!(days[1].start.to_string(), "2023-11-20T00:00:00.000-05:00");
+    }
+}
@@ -0,0 +1,305 @@
+use std::fmt;
+
+use crate::{Concentration, WeatherData};
+
+/// The number of hours the NowCast looks back over.
+const NOWCAST_HOURS: usize = 12;
+
+/// The PM2.5 breakpoints of the US EPA Air Quality Index, as revised in 2024: the lowest and highest concentration in µg/m³ and the lowest and highest index of each category.
+const PM25_BREAKPOINTS: [(f64, f64, u16, u16, AqiCategory); 6] = [
+    (0.0, 9.0, 0, 50, AqiCategory::Good),
+    (9.1, 35.4, 51, 100, AqiCategory::Moderate),
+    (
+        35.5,
+        55.4,
+        101,
+        150,
+        AqiCategory::UnhealthyForSensitiveGroups,
+    ),
+    (55.5, 125.4, 151, 200, AqiCategory::Unhealthy),
+    (125.5, 225.4, 201, 300, AqiCategory::VeryUnhealthy),
+    (225.5, 325.4, 301, 500, AqiCategory::Hazardous),
+];
+
+/// A category of the US EPA Air Quality Index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum AqiCategory {
+    /// An index of 0 to 50.
+    Good,
+    /// An index of 51 to 100.
+    Moderate,
+    /// An index of 101 to 150.
+    UnhealthyForSensitiveGroups,
+    /// An index of 151 to 200.
+    Unhealthy,
+    /// An index of 201 to 300.
+    VeryUnhealthy,
+    /// An index of 301 and above.
+    Hazardous,
+}
+
+impl AqiCategory {
+    /// Returns the name the EPA gives the category, such as `Unhealthy for Sensitive Groups`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            AqiCategory::Good => "Good",
+            AqiCategory::Moderate => "Moderate",
+            AqiCategory::UnhealthyForSensitiveGroups => "Unhealthy for Sensitive Groups",
+            AqiCategory::Unhealthy => "Unhealthy",
+            AqiCategory::VeryUnhealthy => "Very Unhealthy",
+            AqiCategory::Hazardous => "Hazardous",
+        }
+    }
+
+    /// Returns the color the EPA uses for the category, as a hex RGB string such as `#00E400`.
+    pub fn color(&self) -> &'static str {
+        match self {
+            AqiCategory::Good => "#00E400",
+            AqiCategory::Moderate => "#FFFF00",
+            AqiCategory::UnhealthyForSensitiveGroups => "#FF7E00",
+            AqiCategory::Unhealthy => "#FF0000",
+            AqiCategory::VeryUnhealthy => "#8F3F97",
+            AqiCategory::Hazardous => "#7E0023",
+        }
+    }
+}
+
+impl fmt::Display for AqiCategory {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A US EPA Air Quality Index, computed from PM2.5 concentrations.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{Aqi, AqiCategory, Concentration};
+///
+/// let aqi = Aqi::from_pm25(Concentration::from_micrograms_per_cubic_metre(35.0));
+///
+/// assert_eq!(aqi.index, 99);
+/// assert_eq!(aqi.category, AqiCategory::Moderate);
+/// assert_eq!(aqi.color(), "#FFFF00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Aqi {
+    /// The index, from 0 to 500.
+    pub index: u16,
+    /// The category the index falls in.
+    pub category: AqiCategory,
+}
+
+impl Aqi {
+    /// Computes the index of a PM2.5 concentration, which the EPA defines for 24 hour averages. Concentrations above the highest breakpoint are given an index of 500.
+    pub fn from_pm25(concentration: Concentration) -> Aqi {
+        // The EPA truncates concentrations to one decimal before looking them up. The small nudge keeps values such as 9.1, which `f32` stores as 9.0999999, from being truncated to 9.0.
+        let concentration = (f64::from(concentration.micrograms_per_cubic_metre()) * 10.0 + 1e-4)
+            .floor()
+            .max(0.0)
+            / 10.0;
+
+        let (low, high, index_low, index_high, category) = PM25_BREAKPOINTS
+            .iter()
+            .copied()
+            .find(|&(_, high, ..)| concentration <= high)
+            .unwrap_or(PM25_BREAKPOINTS[PM25_BREAKPOINTS.len() - 1]);
+        let concentration = concentration.min(high);
+
+        let index = f64::from(index_high - index_low) / (high - low) * (concentration - low)
+            + f64::from(index_low);
+
+        Aqi {
+            index: index.round() as u16,
+            category,
+        }
+    }
+
+    /// Computes the NowCast index from hourly average PM2.5 concentrations in µg/m³, most recent hour first, with `None` for hours without data. At most 12 hours are used.
+    ///
+    /// The NowCast weighs recent hours more heavily the faster the air quality is changing, so that it follows the real air quality more closely than a plain 24 hour average. Returns `None` unless at least two of the three most recent hours have data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::Aqi;
+    ///
+    /// let steady = Aqi::from_nowcast(&[Some(20.0), Some(20.0), Some(20.0)]).unwrap();
+    /// assert_eq!(steady.index, 71);
+    ///
+    /// assert_eq!(Aqi::from_nowcast(&[Some(20.0), None, None]), None);
+    /// ```
+    pub fn from_nowcast(hourly_averages: &[Option<f32>]) -> Option<Aqi> {
+        let hours = &hourly_averages[..hourly_averages.len().min(NOWCAST_HOURS)];
+
+        if hours.iter().take(3).flatten().count() < 2 {
+            return None;
+        }
+
+        let concentrations = hours.iter().flatten().map(|&value| f64::from(value));
+        let min = concentrations.clone().fold(f64::INFINITY, f64::min);
+        let max = concentrations.fold(f64::NEG_INFINITY, f64::max);
+        let weight = if max > 0.0 { (min / max).max(0.5) } else { 1.0 };
+
+        let (sum, weights) = hours
+            .iter()
+            .enumerate()
+            .filter_map(|(hour, value)| value.map(|value| (hour, f64::from(value))))
+            .fold((0.0, 0.0), |(sum, weights), (hour, value)| {
+                let factor = weight.powi(hour as i32);
+                (sum + factor * value, weights + factor)
+            });
+
+        Some(Aqi::from_pm25(
+            Concentration::from_micrograms_per_cubic_metre((sum / weights) as f32),
+        ))
+    }
+
+    /// Computes the outdoor NowCast index from a sequence of records, such as those returned by [`AmbientWeatherClient::historic`](crate::AmbientWeatherClient::historic), using their `pm25` and `dateutc`. The records may be in any order, and the 12 hours before the newest record are used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{Aqi, AqiCategory, WeatherData};
+    ///
+    /// let records: Vec<WeatherData> = (0..36)
+    ///     .map(|step| serde_json::from_value(serde_json::json!({
+    ///         "dateutc": 1_700_000_000_000i64 + step * 300_000,
+    ///         "pm25": 40.0,
+    ///     })).unwrap())
+    ///     .collect();
+    ///
+    /// let aqi = Aqi::nowcast(&records).unwrap();
+    /// assert_eq!(aqi.category, AqiCategory::UnhealthyForSensitiveGroups);
+    /// ```
+    pub fn nowcast(records: &[WeatherData]) -> Option<Aqi> {
+        Aqi::from_nowcast(&hourly_averages(records, |data| data.pm25))
+    }
+
+    /// Computes the indoor NowCast index from a sequence of records, using their `pm25_in` and `dateutc`, the same way as [`Aqi::nowcast`].
+    pub fn nowcast_indoor(records: &[WeatherData]) -> Option<Aqi> {
+        Aqi::from_nowcast(&hourly_averages(records, |data| data.pm25_in))
+    }
+
+    /// Returns the color the EPA uses for the category of this index, as a hex RGB string such as `#00E400`.
+    pub fn color(&self) -> &'static str {
+        self.category.color()
+    }
+}
+
+impl WeatherData {
+    /// Computes the outdoor US EPA Air Quality Index from the 24 hour average PM2.5 concentration, `pm25_24h`.
+    pub fn epa_aqi(&self) -> Option<Aqi> {
+        self.pm25_24h().map(Aqi::from_pm25)
+    }
+
+    /// Computes the indoor US EPA Air Quality Index from the 24 hour average PM2.5 concentration, `pm25_in_24h`.
+    pub fn indoor_epa_aqi(&self) -> Option<Aqi> {
+        self.pm25_in_24h().map(Aqi::from_pm25)
+    }
+}
+
+/// A private function that averages a reading of the records over each of the 12 hours before the newest record, most recent hour first.
+fn hourly_averages(
+    records: &[WeatherData],
+    reading: impl Fn(&WeatherData) -> Option<f32>,
+) -> Vec<Option<f32>> {
+    let readings: Vec<(i64, f32)> = records
+        .iter()
+        .filter_map(|data| Some((data.dateutc?.as_millis(), reading(data)?)))
+        .collect();
+
+    let Some(newest) = readings.iter().map(|&(time, _)| time).max() else {
+        return Vec::new();
+    };
+
+    let mut hours = [(0.0, 0); NOWCAST_HOURS];
+    for (time, value) in readings {
+        let hour = ((newest - time) / 3_600_000) as usize;
+        if let Some((sum, count)) = hours.get_mut(hour) {
+            *sum += f64::from(value);
+            *count += 1;
+        }
+    }
+
+    hours
+        .iter()
+        .map(|&(sum, count)| (count > 0).then(|| (sum / f64::from(count)) as f32))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pm25(micrograms_per_cubic_metre: f32) -> Aqi {
+        Aqi::from_pm25(Concentration::from_micrograms_per_cubic_metre(
+            micrograms_per_cubic_metre,
+        ))
+    }
+
+    #[test]
+    fn places_each_breakpoint_in_its_category() {
+        let boundaries = [
+            (9.0, 50, AqiCategory::Good),
+            (9.1, 51, AqiCategory::Moderate),
+            (35.4, 100, AqiCategory::Moderate),
+            (35.5, 101, AqiCategory::UnhealthyForSensitiveGroups),
+            (55.4, 150, AqiCategory::UnhealthyForSensitiveGroups),
+            (55.5, 151, AqiCategory::Unhealthy),
+            (125.4, 200, AqiCategory::Unhealthy),
+            (125.5, 201, AqiCategory::VeryUnhealthy),
+            (225.4, 300, AqiCategory::VeryUnhealthy),
+            (225.5, 301, AqiCategory::Hazardous),
+            (325.4, 500, AqiCategory::Hazardous),
+        ];
+
+        for (concentration, index, category) in boundaries {
+            assert_eq!(
+                pm25(concentration),
+                Aqi { index, category },
+                "{concentration}"
+            );
+        }
+    }
+
+    #[test]
+    fn truncates_to_one_decimal() {
+        assert_eq!(pm25(9.09).index, 50);
+        assert_eq!(pm25(35.45).index, 100);
+        assert_eq!(pm25(-1.0).index, 0);
+    }
+
+    #[test]
+    fn caps_the_index_above_the_highest_breakpoint() {
+        for concentration in [325.5, 500.0, 10_000.0] {
+            assert_eq!(
+                pm25(concentration),
+                Aqi {
+                    index: 500,
+                    category: AqiCategory::Hazardous,
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn weighs_fast_changes_no_lower_than_a_half() {
+        // The ratio of 10 to 40 is 0.25, so the weight is raised to 0.5: (10 + 0.5 * 40) / 1.5 = 20
+        assert_eq!(
+            Aqi::from_nowcast(&[Some(10.0), Some(40.0)]),
+            Some(pm25(20.0))
+        );
+    }
+
+    #[test]
+    fn keeps_the_age_of_hours_after_a_missing_hour() {
+        // The third hour keeps the weight of its age, 0.5 squared: (10 + 0.25 * 40) / 1.25 = 16
+        assert_eq!(
+            Aqi::from_nowcast(&[Some(10.0), None, Some(40.0)]),
+            Some(pm25(16.0))
+        );
+        assert_eq!(Aqi::from_nowcast(&[None, Some(20.0), None]), None);
+    }
+}
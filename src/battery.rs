@@ -0,0 +1,278 @@
+use std::fmt;
+
+use crate::WeatherData;
+
+/// The state of a sensor's battery, as Ambient Weather reports it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BatteryStatus {
+    /// The battery is fine.
+    Ok,
+    /// The battery is running low and should be replaced.
+    Low,
+}
+
+impl BatteryStatus {
+    /// Returns the name Ambient Weather gives the status, `OK` or `Low`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatteryStatus::Ok => "OK",
+            BatteryStatus::Low => "Low",
+        }
+    }
+
+    /// A private function that decodes a battery field, where 1 is OK and 0 is low for most sensors, and the other way around for the rest.
+    fn from_field(value: u8, low_is_one: bool) -> Self {
+        if (value == 0) != low_is_one {
+            BatteryStatus::Low
+        } else {
+            BatteryStatus::Ok
+        }
+    }
+}
+
+impl fmt::Display for BatteryStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A battery powered sensor that reports its battery status to Ambient Weather.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BatterySensor {
+    /// The outdoor sensor array, `battout`.
+    Outdoor,
+    /// The indoor sensor, `battin`.
+    Indoor,
+    /// An extra temperature and humidity sensor, `batt1` to `batt10`.
+    Channel(usize),
+    /// A soil moisture sensor, `battsm1` to `battsm10`.
+    Soil(usize),
+    /// A leak detector, `batleak1` to `batleak10`.
+    Leak(usize),
+    /// The outdoor PM2.5 sensor, `batt_25`.
+    Pm25,
+    /// The indoor PM2.5 sensor, `batt_25in`.
+    Pm25Indoor,
+    /// The lightning detector, `batt_lightning`.
+    Lightning,
+    /// The CO₂ sensor, `batt_co2`.
+    Co2,
+    /// The rain gauge, `battrain`.
+    Rain,
+    /// The cellular gateway, `batt_cellgateway`.
+    CellGateway,
+}
+
+impl BatterySensor {
+    /// Returns a label for the sensor, such as `Outdoor sensor` or `Leak detector 2`, for reports.
+    pub fn label(&self) -> String {
+        match self {
+            BatterySensor::Outdoor => String::from("Outdoor sensor"),
+            BatterySensor::Indoor => String::from("Indoor sensor"),
+            BatterySensor::Channel(channel) => format!("Sensor {channel}"),
+            BatterySensor::Soil(channel) => format!("Soil sensor {channel}"),
+            BatterySensor::Leak(channel) => format!("Leak detector {channel}"),
+            BatterySensor::Pm25 => String::from("Outdoor PM2.5 sensor"),
+            BatterySensor::Pm25Indoor => String::from("Indoor PM2.5 sensor"),
+            BatterySensor::Lightning => String::from("Lightning detector"),
+            BatterySensor::Co2 => String::from("CO₂ sensor"),
+            BatterySensor::Rain => String::from("Rain gauge"),
+            BatterySensor::CellGateway => String::from("Cellular gateway"),
+        }
+    }
+
+    /// Returns the Ambient Weather field the sensor reports its battery in, such as `battout` or `batleak2`.
+    pub fn field(&self) -> String {
+        match self {
+            BatterySensor::Outdoor => String::from("battout"),
+            BatterySensor::Indoor => String::from("battin"),
+            BatterySensor::Channel(channel) => format!("batt{channel}"),
+            BatterySensor::Soil(channel) => format!("battsm{channel}"),
+            BatterySensor::Leak(channel) => format!("batleak{channel}"),
+            BatterySensor::Pm25 => String::from("batt_25"),
+            BatterySensor::Pm25Indoor => String::from("batt_25in"),
+            BatterySensor::Lightning => String::from("batt_lightning"),
+            BatterySensor::Co2 => String::from("batt_co2"),
+            BatterySensor::Rain => String::from("battrain"),
+            BatterySensor::CellGateway => String::from("batt_cellgateway"),
+        }
+    }
+
+    /// A private function that returns whether the sensor reports a low battery as 1 rather than 0. Ambient Weather documents leak detectors and the lightning detector this way.
+    fn low_is_one(&self) -> bool {
+        matches!(self, BatterySensor::Leak(_) | BatterySensor::Lightning)
+    }
+}
+
+impl fmt::Display for BatterySensor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.label())
+    }
+}
+
+/// The battery status of one sensor, as returned by [`WeatherData::batteries`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SensorBattery {
+    /// The sensor the battery powers.
+    pub sensor: BatterySensor,
+    /// The state of the battery.
+    pub status: BatteryStatus,
+}
+
+impl SensorBattery {
+    /// Returns `true` if the battery is running low.
+    pub fn is_low(&self) -> bool {
+        self.status == BatteryStatus::Low
+    }
+}
+
+impl fmt::Display for SensorBattery {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.sensor, self.status)
+    }
+}
+
+impl WeatherData {
+    /// Returns the battery status of every sensor that reported one in this record.
+    ///
+    /// Some weather station software, such as Meteobridge, reports every battery with the opposite meaning to Ambient Weather's own consoles. Their statuses will be reversed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{BatterySensor, BatteryStatus, WeatherData};
+    ///
+    /// let data: WeatherData = serde_json::from_str(r#"{ "battout": 1, "batt2": 0, "batleak1": 0 }"#).unwrap();
+    /// let batteries = data.batteries();
+    ///
+    /// assert_eq!(batteries.len(), 3);
+    /// assert_eq!(batteries[1].sensor, BatterySensor::Channel(2));
+    /// assert_eq!(batteries[1].status, BatteryStatus::Low);
+    /// assert_eq!(batteries[2].to_string(), "Leak detector 1: OK");
+    /// ```
+    pub fn batteries(&self) -> Vec<SensorBattery> {
+        let sensors = [
+            (BatterySensor::Outdoor, self.battout),
+            (BatterySensor::Indoor, self.battin),
+        ]
+        .into_iter()
+        .chain(
+            self.channels
+                .battery
+                .iter()
+                .map(|(channel, value)| (BatterySensor::Channel(channel), Some(value))),
+        )
+        .chain(
+            self.channels
+                .soil_battery
+                .iter()
+                .map(|(channel, value)| (BatterySensor::Soil(channel), Some(value))),
+        )
+        .chain(
+            self.channels
+                .leak_battery
+                .iter()
+                .map(|(channel, value)| (BatterySensor::Leak(channel), Some(value))),
+        )
+        .chain([
+            (BatterySensor::Pm25, self.batt_25),
+            (BatterySensor::Pm25Indoor, self.batt_25in),
+            (BatterySensor::Lightning, self.batt_lightning),
+            (BatterySensor::Co2, self.batt_co2),
+            (BatterySensor::Rain, self.battrain),
+            (BatterySensor::CellGateway, self.batt_cellgateway),
+        ]);
+
+        sensors
+            .filter_map(|(sensor, value)| {
+                value.map(|value| SensorBattery {
+                    sensor,
+                    status: BatteryStatus::from_field(value, sensor.low_is_one()),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the sensors whose battery is running low in this record, in the same order as [`WeatherData::batteries`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::WeatherData;
+    ///
+    /// let data: WeatherData = serde_json::from_str(r#"{ "battout": 1, "batt2": 0, "batt_lightning": 1 }"#).unwrap();
+    ///
+    /// let report: Vec<String> = data.low_batteries().iter().map(ToString::to_string).collect();
+    /// assert_eq!(report, ["Sensor 2: Low", "Lightning detector: Low"]);
+    /// ```
+    pub fn low_batteries(&self) -> Vec<SensorBattery> {
+        self.batteries()
+            .into_iter()
+            .filter(SensorBattery::is_low)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn every_sensor() -> Vec<BatterySensor> {
+        let numbered: [fn(usize) -> BatterySensor; 3] = [
+            BatterySensor::Channel,
+            BatterySensor::Soil,
+            BatterySensor::Leak,
+        ];
+
+        [BatterySensor::Outdoor, BatterySensor::Indoor]
+            .into_iter()
+            .chain(numbered.into_iter().flat_map(|sensor| (1..=10).map(sensor)))
+            .chain([
+                BatterySensor::Pm25,
+                BatterySensor::Pm25Indoor,
+                BatterySensor::Lightning,
+                BatterySensor::Co2,
+                BatterySensor::Rain,
+                BatterySensor::CellGateway,
+            ])
+            .collect()
+    }
+
+    fn statuses(value: u8) -> Vec<(BatterySensor, BatteryStatus)> {
+        let record: serde_json::Map<String, serde_json::Value> = every_sensor()
+            .iter()
+            .map(|sensor| (sensor.field(), value.into()))
+            .collect();
+        let data: WeatherData = serde_json::from_value(record.into()).unwrap();
+
+        data.batteries()
+            .into_iter()
+            .map(|battery| (battery.sensor, battery.status))
+            .collect()
+    }
+
+    #[test]
+    fn reads_every_battery_field() {
+        let sensors: Vec<BatterySensor> =
+            statuses(1).into_iter().map(|(sensor, _)| sensor).collect();
+
+        assert_eq!(sensors, every_sensor());
+    }
+
+    #[test]
+    fn reads_each_battery_with_its_own_polarity() {
+        for (value, leak_and_lightning, others) in [
+            (0, BatteryStatus::Ok, BatteryStatus::Low),
+            (1, BatteryStatus::Low, BatteryStatus::Ok),
+        ] {
+            for (sensor, status) in statuses(value) {
+                let expected = match sensor {
+                    BatterySensor::Leak(_) | BatterySensor::Lightning => leak_and_lightning,
+                    _ => others,
+                };
+
+                assert_eq!(status, expected, "{} = {value}", sensor.field());
+            }
+        }
+    }
+}
@@ -0,0 +1,480 @@
+use reqwest::{header::RETRY_AFTER, Proxy, Response, StatusCode};
+use serde_json::Value;
+use std::time::{Duration, SystemTime};
+
+use crate::{
+    device, rate_limit::RateLimiter, AmbientWeatherAPICredentials, AmbientWeatherError, DecodeMode,
+    Device, DeviceSelector, HistoricPages, HistoricQuery, HistoricRecords, RateLimits, RetryPolicy,
+    WeatherData, MAX_HISTORIC_LIMIT,
+};
+
+/// An asynchronous client for the Ambient Weather REST API.
+///
+/// Unlike the blocking functions at the root of this crate, the client does not start its own Tokio runtime, so it can be used from inside any existing async application.
+///
+/// The client owns a pooled [`reqwest::Client`], so connections are reused between calls. Cloning the client is cheap and shares that pool. Use [`AmbientWeatherClient::builder`] to change the base URL, timeouts, user agent, proxy, rate limits or retry policy.
+///
+/// Every request waits for a slot under Ambient Weather's rate limits first; see [`RateLimits`]. Requests that fail with a rate limit, server or network error are retried; see [`RetryPolicy`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use ambient_weather_api::*;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let client = AmbientWeatherClient::new(AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     });
+///
+///     // Get the current temperature from the station named "Backyard"
+///     let latest_data = client.latest(&DeviceSelector::name("Backyard")).await?;
+///     println!("The current temp is: {}F", latest_data.tempf.unwrap_or_default());
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct AmbientWeatherClient {
+    credentials: AmbientWeatherAPICredentials,
+    http: reqwest::Client,
+    base_url: String,
+    rate_limiter: RateLimiter,
+    retry_policy: RetryPolicy,
+    decode_mode: DecodeMode,
+}
+
+impl AmbientWeatherClient {
+    /// Creates a new client from a set of API credentials, using the default settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the underlying HTTP client cannot be initialized, in the same way [`reqwest::Client::new`] does. Use [`AmbientWeatherClient::builder`] to handle that error instead.
+    pub fn new(credentials: AmbientWeatherAPICredentials) -> Self {
+        Self::builder(credentials)
+            .build()
+            .expect("failed to initialize the HTTP client")
+    }
+
+    /// Creates a builder for configuring a client from a set of API credentials.
+    pub fn builder(credentials: AmbientWeatherAPICredentials) -> AmbientWeatherClientBuilder {
+        AmbientWeatherClientBuilder::new(credentials)
+    }
+
+    /// Returns the credentials this client was created with.
+    pub fn credentials(&self) -> &AmbientWeatherAPICredentials {
+        &self.credentials
+    }
+
+    /// Returns the base URL this client sends its requests to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Lists every device registered to the account, along with its metadata and latest data.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] if the request fails, the keys are rejected, the API rate limits the request, or a device cannot be decoded.
+    pub async fn list_devices(&self) -> Result<Vec<Device>, AmbientWeatherError> {
+        let response = self.fetch_json(&self.api_url(""), &[]).await?;
+
+        let devices: Vec<Value> = serde_json::from_value(response)
+            .map_err(|err| AmbientWeatherError::decode(None, err))?;
+
+        devices
+            .iter()
+            .map(|device| device::decode_device(device, self.decode_mode))
+            .collect()
+    }
+
+    /// Gets the latest data for the selected device from the Ambient Weather API.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] if the request fails, the keys are rejected, the API rate limits the request, no device matches `device`, or the device data cannot be decoded.
+    pub async fn latest(
+        &self,
+        device: &DeviceSelector,
+    ) -> Result<WeatherData, AmbientWeatherError> {
+        Ok(self.device(device).await?.last_data)
+    }
+
+    /// Gets the most recent page of historic data for the selected device from the Ambient Weather API, newest record first. Records that cannot be decoded are reported in [`HistoricRecords::errors`].
+    ///
+    /// A device selected by MAC address is queried directly, without listing the account's devices first. The same goes for every other historic call. As the API answers a MAC address it does not know with no records, an empty most recent page for a MAC address is reported as [`AmbientWeatherError::DeviceNotFound`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] under the same conditions as [`AmbientWeatherClient::latest`].
+    pub async fn historic(
+        &self,
+        device: &DeviceSelector,
+    ) -> Result<HistoricRecords, AmbientWeatherError> {
+        self.historic_query(device, &HistoricQuery::new()).await
+    }
+
+    /// Gets a single page of historic data for the selected device, using the end date and limit in `query`. Records are newest first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] under the same conditions as [`AmbientWeatherClient::historic`].
+    pub async fn historic_query(
+        &self,
+        device: &DeviceSelector,
+        query: &HistoricQuery,
+    ) -> Result<HistoricRecords, AmbientWeatherError> {
+        let mac_address = self.mac_address(device).await?;
+        let page = self.historic_page(&mac_address, query).await?;
+
+        let unmatched = matches!(device, DeviceSelector::MacAddress(_))
+            && query.get_end_date().is_none()
+            && page.is_empty()
+            && page.errors.is_empty();
+        if unmatched {
+            return Err(AmbientWeatherError::DeviceNotFound(device.clone()));
+        }
+
+        Ok(page)
+    }
+
+    /// Creates a [`HistoricPages`] that walks backward through the selected device's history, starting from the end date in `query` (or now) and stopping at `start`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] if a device not selected by MAC address cannot be looked up. Errors fetching the pages themselves are returned by [`HistoricPages::next_page`].
+    pub async fn historic_pages(
+        &self,
+        device: &DeviceSelector,
+        query: HistoricQuery,
+        start: SystemTime,
+    ) -> Result<HistoricPages, AmbientWeatherError> {
+        let mac_address = self.mac_address(device).await?;
+
+        Ok(HistoricPages::new(self.clone(), mac_address, query, start))
+    }
+
+    /// Gets every historic record for the selected device from `start` until now, newest record first, fetching as many pages as it takes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] under the same conditions as [`AmbientWeatherClient::historic`].
+    pub async fn historic_since(
+        &self,
+        device: &DeviceSelector,
+        start: SystemTime,
+    ) -> Result<HistoricRecords, AmbientWeatherError> {
+        let query = HistoricQuery::new().limit(MAX_HISTORIC_LIMIT);
+
+        self.historic_pages(device, query, start)
+            .await?
+            .collect_all()
+            .await
+    }
+
+    /// A private function that fetches a single page of historic data for an already resolved device.
+    pub(crate) async fn historic_page(
+        &self,
+        device_mac_address: &str,
+        query: &HistoricQuery,
+    ) -> Result<HistoricRecords, AmbientWeatherError> {
+        let historical_response = self
+            .fetch_json(&self.api_url(device_mac_address), &query.query_pairs())
+            .await?;
+
+        let weather_data_array: Vec<Value> = serde_json::from_value(historical_response)
+            .map_err(|err| AmbientWeatherError::decode(None, err))?;
+
+        Ok(HistoricRecords::decode(
+            weather_data_array,
+            self.decode_mode,
+        ))
+    }
+
+    /// Gets the first device on the account that matches `device`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AmbientWeatherError::DeviceNotFound`] if no device matches, or any error [`AmbientWeatherClient::list_devices`] can return.
+    pub async fn device(&self, device: &DeviceSelector) -> Result<Device, AmbientWeatherError> {
+        let mut devices = self.list_devices().await?;
+
+        match device.position(&devices) {
+            Some(index) => Ok(devices.swap_remove(index)),
+            None => Err(AmbientWeatherError::DeviceNotFound(device.clone())),
+        }
+    }
+
+    /// A private function that returns the MAC address of the selected device, only looking up the account's devices when the selector is not a MAC address already. MAC addresses are matched regardless of case, as [`DeviceSelector::position`] does, and sent in the upper case the API reports them in.
+    async fn mac_address(&self, device: &DeviceSelector) -> Result<String, AmbientWeatherError> {
+        match device {
+            DeviceSelector::MacAddress(mac_address) => Ok(mac_address.to_ascii_uppercase()),
+            device => Ok(self.device(device).await?.mac_address),
+        }
+    }
+
+    /// A private function for crafting the appropriate Ambient Weather API URL.
+    fn api_url(&self, device_mac_address: &str) -> String {
+        format!("{}/v1/devices/{device_mac_address}", self.base_url)
+    }
+
+    /// A private function that fetches a single Ambient Weather API URL and decodes the JSON body, retrying according to the client's [`RetryPolicy`].
+    async fn fetch_json(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<Value, AmbientWeatherError> {
+        let max_attempts = self.retry_policy.max_attempts.max(1);
+        let mut attempt = 1;
+
+        loop {
+            let err = match self.fetch_json_once(url, query).await {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+
+            // Errors that are not worth retrying are returned as they are, even after earlier retries.
+            if !RetryPolicy::is_retryable(&err) {
+                return Err(err);
+            }
+            if attempt >= max_attempts {
+                return Err(if attempt > 1 {
+                    AmbientWeatherError::RetriesExhausted {
+                        attempts: attempt,
+                        last_error: Box::new(err),
+                    }
+                } else {
+                    err
+                });
+            }
+
+            tokio::time::sleep(self.retry_policy.backoff(attempt, &err)).await;
+            attempt += 1;
+        }
+    }
+
+    /// A private function that makes a single attempt at fetching an Ambient Weather API URL.
+    ///
+    /// Each attempt first waits for the rate limiter, so that back to back and concurrent calls stay under Ambient Weather's rate limits.
+    async fn fetch_json_once(
+        &self,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<Value, AmbientWeatherError> {
+        let request = self
+            .http
+            .get(url)
+            .query(&[
+                ("applicationKey", self.credentials.app_key.as_str()),
+                ("apiKey", self.credentials.api_key.as_str()),
+            ])
+            .query(query);
+
+        self.rate_limiter.acquire().await;
+
+        let response = check_response_status(request.send().await?)?;
+        let bytes = response.bytes().await?;
+
+        serde_json::from_slice(&bytes).map_err(|err| AmbientWeatherError::decode(None, err))
+    }
+}
+
+/// A builder for configuring an [`AmbientWeatherClient`].
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::*;
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let api_credentials = AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     };
+///
+///     let client = AmbientWeatherClient::builder(api_credentials)
+///         .base_url("http://localhost:8080")
+///         .timeout(Duration::from_secs(10))
+///         .user_agent("my-weather-dashboard/1.0")
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct AmbientWeatherClientBuilder {
+    credentials: AmbientWeatherAPICredentials,
+    base_url: Option<String>,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: String,
+    proxy: Option<Proxy>,
+    rate_limits: RateLimits,
+    retry_policy: RetryPolicy,
+    decode_mode: DecodeMode,
+}
+
+impl AmbientWeatherClientBuilder {
+    /// A private function for creating a builder with the default settings.
+    fn new(credentials: AmbientWeatherAPICredentials) -> Self {
+        AmbientWeatherClientBuilder {
+            credentials,
+            base_url: None,
+            timeout: Some(DEFAULT_TIMEOUT),
+            connect_timeout: None,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            rate_limits: RateLimits::default(),
+            retry_policy: RetryPolicy::default(),
+            decode_mode: DecodeMode::default(),
+        }
+    }
+
+    /// Sets the base URL requests are sent to, such as `http://localhost:8080` for a local stand-in server.
+    ///
+    /// Defaults to `https://api.ambientweather.net`, or `https://rt.ambientweather.net` when `use_new_api_endpoint` is set in the credentials.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = Some(base_url.into());
+        self
+    }
+
+    /// Sets the total timeout for each request. Defaults to 30 seconds.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Removes the total timeout for each request.
+    pub fn no_timeout(mut self) -> Self {
+        self.timeout = None;
+        self
+    }
+
+    /// Sets the timeout for establishing a connection.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with each request. Defaults to `ambient-weather-api/<version>`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Sends all requests through the given proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets the request rates the client keeps itself under. Defaults to Ambient Weather's published limits of 1 request per second per API key and 3 per Application key.
+    ///
+    /// A rate of zero disables that limit, which is only useful against a local stand-in server.
+    pub fn rate_limits(mut self, rate_limits: RateLimits) -> Self {
+        self.rate_limits = rate_limits;
+        self
+    }
+
+    /// Sets how failed requests are retried. Defaults to [`RetryPolicy::default`]; use [`RetryPolicy::none`] to disable retries.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Sets how strictly records are decoded. Defaults to [`DecodeMode::Lenient`].
+    pub fn decode_mode(mut self, decode_mode: DecodeMode) -> Self {
+        self.decode_mode = decode_mode;
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AmbientWeatherError::Http`] if the underlying HTTP client cannot be initialized.
+    pub fn build(self) -> Result<AmbientWeatherClient, AmbientWeatherError> {
+        let mut http = reqwest::Client::builder().user_agent(self.user_agent);
+
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(connect_timeout) = self.connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+
+        let base_url = match self.base_url {
+            Some(base_url) => base_url.trim_end_matches('/').to_string(),
+            None if self.credentials.use_new_api_endpoint => NEW_BASE_URL.to_string(),
+            None => DEFAULT_BASE_URL.to_string(),
+        };
+
+        let rate_limiter = RateLimiter::new(
+            self.rate_limits,
+            &self.credentials.api_key,
+            &self.credentials.app_key,
+        );
+
+        Ok(AmbientWeatherClient {
+            credentials: self.credentials,
+            http: http.build()?,
+            base_url,
+            rate_limiter,
+            retry_policy: self.retry_policy,
+            decode_mode: self.decode_mode,
+        })
+    }
+}
+
+/// The default Ambient Weather REST API base URL.
+const DEFAULT_BASE_URL: &str = "https://api.ambientweather.net";
+
+/// The base URL used when `use_new_api_endpoint` is set.
+const NEW_BASE_URL: &str = "https://rt.ambientweather.net";
+
+/// The default total timeout for each request.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// The default `User-Agent` header.
+pub(crate) const DEFAULT_USER_AGENT: &str =
+    concat!("ambient-weather-api/", env!("CARGO_PKG_VERSION"));
+
+/// A private function that turns the status of an Ambient Weather API response into the matching error, if any.
+fn check_response_status(response: Response) -> Result<Response, AmbientWeatherError> {
+    match response.status() {
+        StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => Err(AmbientWeatherError::Unauthorized),
+        StatusCode::TOO_MANY_REQUESTS => {
+            let retry_after = response
+                .headers()
+                .get(RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(parse_retry_after);
+
+            Err(AmbientWeatherError::RateLimited { retry_after })
+        }
+        _ => Ok(response.error_for_status()?),
+    }
+}
+
+/// A private function that parses a `Retry-After` header, either a number of seconds or an HTTP date. Dates in the past mean no wait at all.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+
+    match value.parse::<u64>() {
+        Ok(seconds) => Some(Duration::from_secs(seconds)),
+        Err(_) => httpdate::parse_http_date(value).ok().map(|date| {
+            date.duration_since(SystemTime::now())
+                .unwrap_or(Duration::ZERO)
+        }),
+    }
+}
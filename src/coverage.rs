@@ -0,0 +1,343 @@
+use std::time::Duration;
+
+use crate::{
+    aggregate::{local_records, window_start},
+    timestamp::{civil_from_days, days_from_civil},
+    LocalDateTime, Period, TimeZone, Timestamp, WeatherData,
+};
+
+/// A stretch of time in which a station missed at least one report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Gap {
+    /// The time of the last record before the gap.
+    pub start: Timestamp,
+    /// The time of the first record after the gap.
+    pub end: Timestamp,
+}
+
+impl Gap {
+    /// Returns the time from the last record before the gap to the first record after it.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis((self.end.as_millis() - self.start.as_millis()).max(0) as u64)
+    }
+}
+
+/// How many of the reports expected on one local day a station actually made.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DayCoverage {
+    /// The local midnight the day starts at.
+    pub date: LocalDateTime,
+    /// The number of records the station made during the day.
+    pub records: usize,
+    /// The number of records the station was expected to make during the day, going by its reporting interval.
+    pub expected: usize,
+}
+
+impl DayCoverage {
+    /// Returns the share of the expected reports the station made, from 0 to 100%.
+    pub fn percentage(&self) -> f32 {
+        (self.records as f32 / self.expected as f32 * 100.0).min(100.0)
+    }
+}
+
+/// The gaps in a series of records and how much of each day they cover, for finding when a station stopped reporting.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{Coverage, WeatherData};
+/// use std::time::Duration;
+///
+/// // A station reporting every 5 minutes over a day, that went quiet from 06:00 to 12:00
+/// let records: Vec<WeatherData> = (0..288)
+///     .filter(|step| !(72..144).contains(step))
+///     .map(|step| serde_json::from_value(serde_json::json!({
+///         "dateutc": 1_697_328_000_000i64 + step * 300_000,
+///         "tz": "UTC",
+///     })).unwrap())
+///     .collect();
+///
+/// let coverage = Coverage::from_records(&records).unwrap();
+///
+/// assert_eq!(coverage.interval, Duration::from_secs(300));
+/// assert_eq!(coverage.gaps.len(), 1);
+/// assert_eq!(coverage.gaps[0].start.to_string(), "2023-10-15T05:55:00.000Z");
+/// assert_eq!(coverage.gaps[0].duration(), Duration::from_secs(6 * 60 * 60 + 300));
+/// assert_eq!(coverage.days[0].percentage(), 75.0);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    /// The interval the station reports at, inferred from the median spacing of its records.
+    pub interval: Duration,
+    /// Every gap in the records, oldest first.
+    pub gaps: Vec<Gap>,
+    /// The coverage of every local day from the first record to the last, including days without any records, oldest first.
+    pub days: Vec<DayCoverage>,
+}
+
+impl Coverage {
+    /// How many reporting intervals records may be apart before [`Coverage::from_records`] counts the time between them as a gap.
+    pub const DEFAULT_GAP_FACTOR: f64 = 1.5;
+
+    /// Finds the reporting interval, the gaps and the daily coverage of a series of records, such as those returned by [`AmbientWeatherClient::historic_since`](crate::AmbientWeatherClient::historic_since). The records may be in any order.
+    ///
+    /// The interval is the median time between consecutive records, which is not thrown off by the gaps themselves. A gap is any spacing of more than [`Coverage::DEFAULT_GAP_FACTOR`] intervals. Days follow the wall clock of each record's time zone, `tz`, or UTC for records without a known time zone, so days with a daylight saving time change expect an hour more or fewer reports. The first and last day only count from the first record and up to the last record.
+    ///
+    /// Returns `None` if there are fewer than two records with distinct `dateutc`s to infer the interval from.
+    pub fn from_records(records: &[WeatherData]) -> Option<Coverage> {
+        Coverage::from_records_with_gap_factor(records, Coverage::DEFAULT_GAP_FACTOR)
+    }
+
+    /// Works like [`Coverage::from_records`], but counts any spacing of more than `gap_factor` reporting intervals as a gap, for stations whose reports are more or less regular than usual.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{Coverage, WeatherData};
+    ///
+    /// // A station reporting every 5 minutes that once reported 10 minutes late
+    /// let records: Vec<WeatherData> = [0, 1, 2, 4, 5, 6]
+    ///     .into_iter()
+    ///     .map(|step| serde_json::from_value(serde_json::json!({
+    ///         "dateutc": 1_697_328_000_000i64 + step * 300_000,
+    ///     })).unwrap())
+    ///     .collect();
+    ///
+    /// assert_eq!(Coverage::from_records(&records).unwrap().gaps.len(), 1);
+    /// assert!(Coverage::from_records_with_gap_factor(&records, 3.0).unwrap().gaps.is_empty());
+    /// ```
+    pub fn from_records_with_gap_factor(
+        records: &[WeatherData],
+        gap_factor: f64,
+    ) -> Option<Coverage> {
+        let mut records: Vec<(Timestamp, LocalDateTime, Option<&TimeZone>)> =
+            local_records(records)
+                .into_iter()
+                .map(|(time, local_time, data)| (time, local_time, data.tz.as_ref()))
+                .collect();
+        records.dedup_by_key(|&mut (time, ..)| time);
+
+        let mut spacings: Vec<i64> = records
+            .windows(2)
+            .map(|pair| pair[1].0.as_millis() - pair[0].0.as_millis())
+            .collect();
+        spacings.sort_unstable();
+        let interval = *spacings.get(spacings.len() / 2)?;
+
+        let gaps = records
+            .windows(2)
+            .filter(|pair| {
+                (pair[1].0.as_millis() - pair[0].0.as_millis()) as f64
+                    > interval as f64 * gap_factor
+            })
+            .map(|pair| Gap {
+                start: pair[0].0,
+                end: pair[1].0,
+            })
+            .collect();
+
+        let series_start = records[0].0.as_millis();
+        let series_end = records[records.len() - 1].0.as_millis() + interval;
+
+        let day_coverage = |date: LocalDateTime, time_zone: Option<&TimeZone>, records: usize| {
+            let date = window_start(date, Period::Day, time_zone);
+
+            // Days with a daylight saving time change are an hour shorter or longer, so the day ends at the next midnight at the offset in force then.
+            let day_start = date.timestamp().as_millis();
+            let day_end = window_start(next_date(date), Period::Day, time_zone)
+                .timestamp()
+                .as_millis();
+            let span = day_end.min(series_end) - day_start.max(series_start);
+
+            DayCoverage {
+                date,
+                records,
+                expected: ((span as f64 / interval as f64).round() as usize).max(1),
+            }
+        };
+
+        let mut days = Vec::new();
+        let mut previous_day: Option<(LocalDateTime, Option<&TimeZone>)> = None;
+        for day in records
+            .chunk_by(|(_, a, _), (_, b, _)| (a.year, a.month, a.day) == (b.year, b.month, b.day))
+        {
+            let (_, first, time_zone) = day[0];
+
+            // Days without a single record are reported too, as they are the longest outages of all.
+            if let Some((previous, previous_time_zone)) = previous_day {
+                let mut date = next_date(previous);
+                while (date.year, date.month, date.day) < (first.year, first.month, first.day) {
+                    days.push(day_coverage(date, previous_time_zone, 0));
+                    date = next_date(date);
+                }
+            }
+
+            days.push(day_coverage(first, time_zone, day.len()));
+            previous_day = Some((first, time_zone));
+        }
+
+        Some(Coverage {
+            interval: Duration::from_millis(interval as u64),
+            gaps,
+            days,
+        })
+    }
+
+    /// Returns the days on which the station made less than the given percentage of its expected reports.
+    pub fn days_below(&self, percentage: f32) -> impl Iterator<Item = &DayCoverage> {
+        self.days
+            .iter()
+            .filter(move |day| day.percentage() < percentage)
+    }
+}
+
+/// A private function that returns the same wall clock time on the next calendar day, at the same offset.
+fn next_date(date: LocalDateTime) -> LocalDateTime {
+    let (year, month, day) =
+        civil_from_days(days_from_civil(date.year, date.month.into(), date.day.into()) + 1);
+
+    LocalDateTime {
+        year,
+        month: month as u8,
+        day: day as u8,
+        ..date
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3_600_000;
+
+    /// Hourly records from `start` up to and including `end`, in the given time zone.
+    fn hourly(start: &str, end: &str, tz: &str) -> Vec<WeatherData> {
+        let start = Timestamp::parse(start).unwrap().as_millis();
+        let end = Timestamp::parse(end).unwrap().as_millis();
+
+        (start..=end)
+            .step_by(HOUR as usize)
+            .map(|time| {
+                serde_json::from_value(serde_json::json!({ "dateutc": time, "tz": tz })).unwrap()
+            })
+            .collect()
+    }
+
+    fn counts(coverage: &Coverage) -> Vec<(String, usize, usize)> {
+        coverage
+            .days
+            .iter()
+            .map(|day| (day.date.to_string(), day.records, day.expected))
+            .collect()
+    }
+
+    #[test]
+    fn expects_an_hour_more_on_the_day_clocks_fall_back() {
+        let records = hourly(
+            "2023-11-05T04:00:00.000Z",
+            "2023-11-06T04:00:00.000Z",
+            "America/New_York",
+        );
+
+        let coverage = Coverage::from_records(&records).unwrap();
+
+        assert_eq!(
+            counts(&coverage)[0],
+            (String::from("2023-11-05T00:00:00.000-04:00"), 25, 25)
+        );
+        assert_eq!(coverage.days[0].percentage(), 100.0);
+    }
+
+    #[test]
+    fn expects_an_hour_less_on_the_day_clocks_spring_forward() {
+        // The first record of the day is still on standard time, while the next midnight is on daylight saving time
+        let records = hourly(
+            "2023-03-12T05:00:00.000Z",
+            "2023-03-13T04:00:00.000Z",
+            "America/New_York",
+        );
+
+        let coverage = Coverage::from_records(&records).unwrap();
+
+        assert_eq!(
+            counts(&coverage)[0],
+            (String::from("2023-03-12T00:00:00.000-05:00"), 23, 23)
+        );
+    }
+
+    #[test]
+    fn counts_partial_first_and_last_days_from_and_up_to_the_records() {
+        let records = hourly(
+            "2023-11-04T12:00:00.000Z",
+            "2023-11-07T12:00:00.000Z",
+            "America/New_York",
+        );
+
+        let coverage = Coverage::from_records(&records).unwrap();
+
+        assert_eq!(
+            counts(&coverage),
+            [
+                (String::from("2023-11-04T00:00:00.000-04:00"), 16, 16),
+                (String::from("2023-11-05T00:00:00.000-04:00"), 25, 25),
+                (String::from("2023-11-06T00:00:00.000-05:00"), 24, 24),
+                (String::from("2023-11-07T00:00:00.000-05:00"), 8, 8),
+            ]
+        );
+        assert_eq!(coverage.days_below(100.0).count(), 0);
+    }
+
+    #[test]
+    fn reports_days_without_any_records() {
+        let mut records = hourly(
+            "2023-10-15T00:00:00.000Z",
+            "2023-10-18T23:00:00.000Z",
+            "UTC",
+        );
+        // No reports at all on 16 and 17 October
+        records.retain(|data| {
+            let day = data.dateutc.unwrap().to_utc().day;
+            day != 16 && day != 17
+        });
+
+        let coverage = Coverage::from_records(&records).unwrap();
+
+        assert_eq!(
+            counts(&coverage),
+            [
+                (String::from("2023-10-15T00:00:00.000Z"), 24, 24),
+                (String::from("2023-10-16T00:00:00.000Z"), 0, 24),
+                (String::from("2023-10-17T00:00:00.000Z"), 0, 24),
+                (String::from("2023-10-18T00:00:00.000Z"), 24, 24),
+            ]
+        );
+        let outages: Vec<u8> = coverage.days_below(50.0).map(|day| day.date.day).collect();
+        assert_eq!(outages, [16, 17]);
+    }
+
+    #[test]
+    fn finds_gaps_beyond_the_gap_factor() {
+        let mut records = hourly(
+            "2023-10-15T00:00:00.000Z",
+            "2023-10-15T12:00:00.000Z",
+            "UTC",
+        );
+        // Two hours without a report, then three
+        records.remove(3);
+        records.remove(6);
+        records.remove(6);
+
+        let gaps = |gap_factor| {
+            Coverage::from_records_with_gap_factor(&records, gap_factor)
+                .unwrap()
+                .gaps
+                .iter()
+                .map(|gap| gap.duration().as_secs() / 3600)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(gaps(Coverage::DEFAULT_GAP_FACTOR), [2, 3]);
+        assert_eq!(gaps(2.0), [3]);
+        assert_eq!(gaps(3.0), Vec::<u64>::new());
+    }
+}
@@ -0,0 +1,329 @@
+use serde::Serialize;
+
+use crate::{
+    aggregate::{local_records, window_start},
+    json_number, LocalDateTime, Period, Timestamp, WeatherData,
+};
+
+/// The highlights of one local day of weather, built from historic records, such as for a daily report.
+///
+/// Readings are in the units Ambient Weather reports in: °F, mph, inches and inches of mercury. When serialized, missing readings are left out.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{DailySummary, WeatherData};
+///
+/// let records: Vec<WeatherData> = [
+///     ("2023-10-15T10:00:00.000Z", 48.2, 0.0, 0.0),
+///     ("2023-10-15T14:00:00.000Z", 55.0, 0.2, 12.4),
+///     ("2023-10-15T19:00:00.000Z", 61.7, 0.5, 8.9),
+/// ]
+/// .into_iter()
+/// .map(|(date, tempf, dailyrainin, windgustmph)| serde_json::from_value(serde_json::json!({
+///     "dateutc": date,
+///     "tempf": tempf,
+///     "dailyrainin": dailyrainin,
+///     "windgustmph": windgustmph,
+///     "windgustdir": 290,
+///     "tz": "America/New_York",
+/// })).unwrap())
+/// .collect();
+///
+/// let summary = DailySummary::for_date(&records, 2023, 10, 15).unwrap();
+///
+/// assert_eq!(summary.high_temperature, Some(61.7));
+/// assert_eq!(summary.high_temperature_time.unwrap().to_string(), "2023-10-15T15:00:00.000-04:00");
+/// assert_eq!(summary.max_gust, Some(12.4));
+/// assert_eq!(summary.max_gust_direction, Some(290));
+/// assert_eq!(summary.rain, 0.5);
+///
+/// let json = serde_json::to_value(&summary).unwrap();
+/// assert_eq!(json["date"], "2023-10-15T00:00:00.000-04:00");
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DailySummary {
+    /// The local midnight the day starts at.
+    pub date: LocalDateTime,
+    /// The number of records the summary was built from.
+    pub records: usize,
+    /// The highest outdoor temperature, in °F.
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub high_temperature: Option<f32>,
+    /// When the highest outdoor temperature was first reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub high_temperature_time: Option<LocalDateTime>,
+    /// The lowest outdoor temperature, in °F.
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub low_temperature: Option<f32>,
+    /// When the lowest outdoor temperature was first reached.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub low_temperature_time: Option<LocalDateTime>,
+    /// The strongest wind gust, in mph.
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub max_gust: Option<f32>,
+    /// The direction the strongest wind gust came from, in degrees.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gust_direction: Option<u16>,
+    /// When the strongest wind gust was first reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_gust_time: Option<LocalDateTime>,
+    /// The rain that fell during the day, in inches.
+    #[serde(serialize_with = "json_number::serialize")]
+    pub rain: f32,
+    /// The mean relative pressure, in inches of mercury.
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub mean_pressure: Option<f32>,
+    /// The highest UV index.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub peak_uv: Option<u8>,
+    /// The highest solar radiation, in W/m².
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub peak_solar_radiation: Option<f32>,
+    /// The number of lightning strikes detected during the day.
+    pub lightning_strikes: u32,
+}
+
+impl DailySummary {
+    /// Summarizes the records of one local date, such as those returned by [`AmbientWeatherClient::historic_since`](crate::AmbientWeatherClient::historic_since). Records of other dates are ignored.
+    ///
+    /// The date follows the wall clock of each record's time zone, `tz`, or UTC for records without a known time zone. Returns `None` if no record falls on the date.
+    ///
+    /// Rain and lightning strikes come from the `dailyrainin` and `lightning_day` counters, which the station resets at its own local midnight. On a day that falls back to UTC, the counters usually started before the UTC day did, so only what they rose by after the day's first record is counted.
+    pub fn for_date(
+        records: &[WeatherData],
+        year: i64,
+        month: u8,
+        day: u8,
+    ) -> Option<DailySummary> {
+        let day_records: Vec<(Timestamp, LocalDateTime, &WeatherData)> = local_records(records)
+            .into_iter()
+            .filter(|(_, local_time, _)| {
+                (local_time.year, local_time.month, local_time.day) == (year, month, day)
+            })
+            .collect();
+
+        summarize(&day_records)
+    }
+
+    /// Summarizes every local date the records fall on, oldest first, the same way as [`DailySummary::for_date`].
+    pub fn from_records(records: &[WeatherData]) -> Vec<DailySummary> {
+        let records = local_records(records);
+
+        records
+            .chunk_by(|(_, a, _), (_, b, _)| (a.year, a.month, a.day) == (b.year, b.month, b.day))
+            .filter_map(summarize)
+            .collect()
+    }
+}
+
+/// Returns the total a cumulative counter, such as `dailyrainin`, rose by over a sequence of readings that starts from zero, treating drops the same way as [`counter_increase`]. When the counter did not start from zero at the first reading, `from_zero` leaves out the first reading's count.
+pub(crate) fn counter_total(readings: impl IntoIterator<Item = f64>, from_zero: bool) -> f64 {
+    let mut readings = readings.into_iter().peekable();
+    let start = match (from_zero, readings.peek()) {
+        (false, Some(&first)) => first,
+        _ => 0.0,
+    };
+
+    readings
+        .fold((0.0, start), |(total, previous), reading| {
+            (total + counter_increase(previous, reading), reading)
+        })
+        .0
+}
+
+/// Returns how much a cumulative counter, such as `totalrainin`, rose by between two readings.
+///
+/// Any reading lower than the one before it is taken to be a reset of the counter, such as `dailyrainin` at midnight, and the counter is taken to have counted up from zero since.
+pub(crate) fn counter_increase(previous: f64, reading: f64) -> f64 {
+    if reading >= previous {
+        reading - previous
+    } else {
+        reading
+    }
+}
+
+/// A private function that summarizes the records of one day, oldest first. Returns `None` if there are none.
+fn summarize(records: &[(Timestamp, LocalDateTime, &WeatherData)]) -> Option<DailySummary> {
+    let &(_, first, first_data) = records.first()?;
+
+    // The counters reset at the station's own midnight, which the day only starts at when its time zone is known.
+    let from_zero = first_data.local_time().is_some();
+
+    // The first record to reach an extreme wins ties, so that its time is when the extreme was first reached.
+    let extreme = |reading: fn(&WeatherData) -> Option<f32>, highest: bool| {
+        records
+            .iter()
+            .filter_map(|&(_, local_time, data)| Some((reading(data)?, local_time, data)))
+            .reduce(|best, candidate| {
+                let better = if highest {
+                    candidate.0 > best.0
+                } else {
+                    candidate.0 < best.0
+                };
+                if better {
+                    candidate
+                } else {
+                    best
+                }
+            })
+    };
+
+    let high = extreme(|data| data.tempf, true);
+    let low = extreme(|data| data.tempf, false);
+    let gust = extreme(|data| data.windgustmph, true);
+
+    let pressures: Vec<f64> = records
+        .iter()
+        .filter_map(|(_, _, data)| data.baromrelin.map(f64::from))
+        .collect();
+
+    Some(DailySummary {
+        date: window_start(first, Period::Day, first_data.tz.as_ref()),
+        records: records.len(),
+        high_temperature: high.map(|(value, ..)| value),
+        high_temperature_time: high.map(|(_, time, _)| time),
+        low_temperature: low.map(|(value, ..)| value),
+        low_temperature_time: low.map(|(_, time, _)| time),
+        max_gust: gust.map(|(value, ..)| value),
+        max_gust_direction: gust.and_then(|(_, _, data)| data.windgustdir),
+        max_gust_time: gust.map(|(_, time, _)| time),
+        rain: counter_total(
+            records
+                .iter()
+                .filter_map(|(_, _, data)| data.dailyrainin.map(f64::from)),
+            from_zero,
+        ) as f32,
+        mean_pressure: (!pressures.is_empty())
+            .then(|| (pressures.iter().sum::<f64>() / pressures.len() as f64) as f32),
+        peak_uv: records.iter().filter_map(|(_, _, data)| data.uv).max(),
+        peak_solar_radiation: records
+            .iter()
+            .filter_map(|(_, _, data)| data.solarradiation)
+            .reduce(f32::max),
+        lightning_strikes: counter_total(
+            records
+                .iter()
+                .filter_map(|(_, _, data)| data.lightning_day.map(f64::from)),
+            from_zero,
+        ) as u32,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rounded(value: f64) -> f64 {
+        (value * 100.0).round() / 100.0
+    }
+
+    #[test]
+    fn counts_rises_of_a_counter() {
+        assert_eq!(rounded(counter_increase(0.10, 0.35)), 0.25);
+        assert_eq!(counter_increase(0.35, 0.35), 0.0);
+    }
+
+    #[test]
+    fn counts_up_from_zero_after_any_drop() {
+        // A reset at midnight to more than half of what the counter had reached
+        assert_eq!(counter_increase(0.30, 0.20), 0.20);
+        // A reset at midnight to next to nothing
+        assert_eq!(counter_increase(0.35, 0.02), 0.02);
+        assert_eq!(counter_increase(0.35, 0.0), 0.0);
+    }
+
+    #[test]
+    fn totals_readings_across_resets() {
+        assert_eq!(rounded(counter_total([0.1, 0.3, 0.2, 0.25], true)), 0.55);
+        assert_eq!(counter_total([4.0, 9.0, 2.0], true), 11.0);
+        assert_eq!(counter_total([], true), 0.0);
+    }
+
+    #[test]
+    fn leaves_out_what_a_counter_held_before_the_first_reading() {
+        assert_eq!(rounded(counter_total([0.3, 0.4, 0.1, 0.2], false)), 0.3);
+        assert_eq!(counter_total([4.0], false), 0.0);
+        assert_eq!(counter_total([], false), 0.0);
+    }
+
+    #[test]
+    fn summarizes_rain_that_restarted_within_the_day() {
+        let records = records(
+            &[
+                ("2023-10-15T08:00:00.000Z", 0.30),
+                ("2023-10-15T12:00:00.000Z", 0.10),
+                ("2023-10-15T16:00:00.000Z", 0.20),
+            ],
+            Some("UTC"),
+        );
+
+        let summary = DailySummary::for_date(&records, 2023, 10, 15).unwrap();
+
+        assert_eq!((summary.rain * 100.0).round(), 50.0);
+    }
+
+    fn records(readings: &[(&str, f32)], tz: Option<&str>) -> Vec<WeatherData> {
+        readings
+            .iter()
+            .map(|&(date, dailyrainin)| {
+                let mut record = serde_json::json!({ "dateutc": date, "dailyrainin": dailyrainin });
+                if let Some(tz) = tz {
+                    record["tz"] = tz.into();
+                }
+                serde_json::from_value(record).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn starts_the_day_at_the_offset_of_local_midnight() {
+        // New York left daylight saving time at 02:00 on 5 November 2023, after midnight but before the first record
+        let records = records(
+            &[("2023-11-05T12:00:00.000Z", 0.0)],
+            Some("America/New_York"),
+        );
+
+        let summary = DailySummary::for_date(&records, 2023, 11, 5).unwrap();
+
+        assert_eq!(summary.date.to_string(), "2023-11-05T00:00:00.000-04:00");
+        assert_eq!(
+            summary.date.timestamp(),
+            Timestamp::parse("2023-11-05T04:00:00Z").unwrap()
+        );
+    }
+
+    #[test]
+    fn counts_only_rain_after_the_first_record_of_a_utc_day() {
+        // The station's own midnight is unknown, so 0.30 in may have fallen before the UTC day began
+        let records = records(
+            &[
+                ("2023-10-15T00:00:00.000Z", 0.30),
+                ("2023-10-15T03:00:00.000Z", 0.40),
+                ("2023-10-15T06:00:00.000Z", 0.10),
+                ("2023-10-15T09:00:00.000Z", 0.20),
+            ],
+            None,
+        );
+
+        let summary = DailySummary::for_date(&records, 2023, 10, 15).unwrap();
+
+        assert_eq!(summary.date.to_string(), "2023-10-15T00:00:00.000Z");
+        assert_eq!((summary.rain * 100.0).round(), 30.0);
+    }
+}
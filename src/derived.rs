@@ -0,0 +1,327 @@
+use crate::{Length, Pressure, Speed, Temperature, WeatherData};
+
+/// Values derived from the temperature, humidity, wind and dew point of a [`WeatherData`] record, as returned by [`WeatherData::derived`] and [`WeatherData::derived_indoor`].
+///
+/// Each value is `None` when a reading it needs is missing from the record. Outdoors, the wind chill and apparent temperature are only derived from records that report the wind, and never taken to be calm air, while the cloud base height only needs the temperature and humidity. Values that only make sense outdoors, such as the wind chill and the cloud base height, are always `None` indoors.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DerivedWeather {
+    /// The dew point, see [`Temperature::dew_point`].
+    pub dew_point: Option<Temperature>,
+    /// The heat index, see [`Temperature::heat_index`].
+    pub heat_index: Option<Temperature>,
+    /// The wind chill, see [`Temperature::wind_chill`].
+    pub wind_chill: Option<Temperature>,
+    /// The apparent temperature, see [`Temperature::apparent_temperature`].
+    pub apparent_temperature: Option<Temperature>,
+    /// The wet-bulb temperature, see [`Temperature::wet_bulb`].
+    pub wet_bulb: Option<Temperature>,
+    /// The absolute humidity in g/m³, see [`Temperature::absolute_humidity`].
+    pub absolute_humidity: Option<f32>,
+    /// The partial pressure of water vapor, see [`Temperature::vapor_pressure`].
+    pub vapor_pressure: Option<Pressure>,
+    /// The height of the cloud base above the station, see [`Temperature::cloud_base`].
+    pub cloud_base: Option<Length>,
+}
+
+impl Temperature {
+    /// Returns the saturation vapor pressure of water at this temperature, using Bolton's formula.
+    pub fn saturation_vapor_pressure(&self) -> Pressure {
+        let celsius = f64::from(self.celsius());
+
+        Pressure::from_hectopascals((6.112 * (17.67 * celsius / (celsius + 243.5)).exp()) as f32)
+    }
+
+    /// Returns the partial pressure of water vapor in air at this temperature and the given relative humidity in %.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::Temperature;
+    ///
+    /// let vapor_pressure = Temperature::from_celsius(20.0).vapor_pressure(50.0);
+    ///
+    /// assert_eq!(vapor_pressure.hectopascals().round(), 12.0);
+    /// ```
+    pub fn vapor_pressure(&self, humidity: f32) -> Pressure {
+        let saturation = f64::from(self.saturation_vapor_pressure().hectopascals());
+
+        Pressure::from_hectopascals((saturation * f64::from(humidity) / 100.0) as f32)
+    }
+
+    /// Returns the mass of water vapor in a cubic metre of air at this temperature and the given relative humidity in %, in g/m³.
+    pub fn absolute_humidity(&self, humidity: f32) -> f32 {
+        let vapor_pressure = f64::from(self.vapor_pressure(humidity).hectopascals());
+
+        // The ideal gas law, with the specific gas constant of water vapor folded into the factor.
+        (216.7 * vapor_pressure / f64::from(self.kelvin())) as f32
+    }
+
+    /// Returns the dew point of air at this temperature and the given relative humidity in %, using the Magnus formula. The humidity must be above 0.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::Temperature;
+    ///
+    /// let dew_point = Temperature::from_celsius(25.0).dew_point(60.0);
+    ///
+    /// assert_eq!(dew_point.celsius().round(), 17.0);
+    /// ```
+    pub fn dew_point(&self, humidity: f32) -> Temperature {
+        const B: f64 = 17.625;
+        const C: f64 = 243.04;
+
+        let celsius = f64::from(self.celsius());
+        let gamma = (f64::from(humidity) / 100.0).ln() + B * celsius / (C + celsius);
+
+        Temperature::from_celsius((C * gamma / (B - gamma)) as f32)
+    }
+
+    /// Returns the heat index of air at this temperature and the given relative humidity in %, using the US National Weather Service's algorithm.
+    ///
+    /// Below about 80 °F the heat index is close to the temperature itself, and a simpler formula is used, as the National Weather Service does.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::Temperature;
+    ///
+    /// let heat_index = Temperature::from_fahrenheit(90.0).heat_index(70.0);
+    ///
+    /// assert_eq!(heat_index.fahrenheit().round(), 106.0);
+    /// ```
+    pub fn heat_index(&self, humidity: f32) -> Temperature {
+        let t = f64::from(self.fahrenheit());
+        let rh = f64::from(humidity);
+
+        let simple = 0.5 * (t + 61.0 + (t - 68.0) * 1.2 + rh * 0.094);
+        if (simple + t) / 2.0 < 80.0 {
+            return Temperature::from_fahrenheit(simple as f32);
+        }
+
+        let mut heat_index = -42.379 + 2.049_015_23 * t + 10.143_331_27 * rh
+            - 0.224_755_41 * t * rh
+            - 0.006_837_83 * t * t
+            - 0.054_817_17 * rh * rh
+            + 0.001_228_74 * t * t * rh
+            + 0.000_852_82 * t * rh * rh
+            - 0.000_001_99 * t * t * rh * rh;
+
+        if rh < 13.0 && (80.0..=112.0).contains(&t) {
+            heat_index -= (13.0 - rh) / 4.0 * ((17.0 - (t - 95.0).abs()) / 17.0).sqrt();
+        } else if rh > 85.0 && (80.0..=87.0).contains(&t) {
+            heat_index += (rh - 85.0) / 10.0 * ((87.0 - t) / 5.0);
+        }
+
+        Temperature::from_fahrenheit(heat_index as f32)
+    }
+
+    /// Returns the wind chill at this temperature and the given wind speed, using the formula of the US National Weather Service and Environment Canada.
+    ///
+    /// Wind chill is only defined at or below 50 °F with wind of at least 3 mph. Outside of that range the temperature itself is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{Speed, Temperature};
+    ///
+    /// let wind_chill = Temperature::from_fahrenheit(20.0).wind_chill(Speed::from_miles_per_hour(15.0));
+    ///
+    /// assert_eq!(wind_chill.fahrenheit().round(), 6.0);
+    /// ```
+    pub fn wind_chill(&self, wind_speed: Speed) -> Temperature {
+        let t = f64::from(self.fahrenheit());
+        let v = f64::from(wind_speed.miles_per_hour());
+
+        if t > 50.0 || v < 3.0 {
+            return *self;
+        }
+
+        let v = v.powf(0.16);
+
+        Temperature::from_fahrenheit((35.74 + 0.6215 * t - 35.75 * v + 0.4275 * t * v) as f32)
+    }
+
+    /// Returns the apparent temperature at this temperature, the given relative humidity in % and the given wind speed, using Steadman's formula as published by the Australian Bureau of Meteorology. Unlike the heat index and wind chill, it is defined at any temperature.
+    pub fn apparent_temperature(&self, humidity: f32, wind_speed: Speed) -> Temperature {
+        let celsius = f64::from(self.celsius());
+        let vapor_pressure = f64::from(self.vapor_pressure(humidity).hectopascals());
+        let wind_speed = f64::from(wind_speed.metres_per_second());
+
+        Temperature::from_celsius(
+            (celsius + 0.33 * vapor_pressure - 0.70 * wind_speed - 4.00) as f32,
+        )
+    }
+
+    /// Returns the wet-bulb temperature of air at this temperature and the given relative humidity in %, using Stull's formula, which holds at sea level pressure between 5% and 99% humidity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::Temperature;
+    ///
+    /// let wet_bulb = Temperature::from_celsius(20.0).wet_bulb(50.0);
+    ///
+    /// assert_eq!(wet_bulb.celsius().round(), 14.0);
+    /// ```
+    pub fn wet_bulb(&self, humidity: f32) -> Temperature {
+        let t = f64::from(self.celsius());
+        let rh = f64::from(humidity);
+
+        let wet_bulb = t * (0.151_977 * (rh + 8.313_659).sqrt()).atan() + (t + rh).atan()
+            - (rh - 1.676_331).atan()
+            + 0.003_918_38 * rh.powf(1.5) * (0.023_101 * rh).atan()
+            - 4.686_035;
+
+        Temperature::from_celsius(wet_bulb as f32)
+    }
+
+    /// Returns the height above the station of the base of cumulus clouds, estimated from the spread between this temperature and the dew point. The spread shrinks by about 1 °C for every 125 m a parcel of air rises.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::Temperature;
+    ///
+    /// let cloud_base = Temperature::from_celsius(20.0).cloud_base(Temperature::from_celsius(12.0));
+    ///
+    /// assert_eq!(cloud_base.metres(), 1000.0);
+    /// ```
+    pub fn cloud_base(&self, dew_point: Temperature) -> Length {
+        let spread = f64::from(self.celsius()) - f64::from(dew_point.celsius());
+
+        Length::from_metres((spread.max(0.0) * 125.0) as f32)
+    }
+}
+
+impl WeatherData {
+    /// Derives the dew point, heat index, wind chill and more from the outdoor readings of this record.
+    ///
+    /// The values are always computed from `tempf`, `humidity` and `windspeedmph`, even when the record also carries `dewPoint` or `feelsLike`, so that they are consistent across devices and historic records.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::WeatherData;
+    ///
+    /// let data: WeatherData = serde_json::from_str(r#"{ "tempf": 77.0, "humidity": 60, "windspeedmph": 5.0 }"#).unwrap();
+    /// let derived = data.derived();
+    ///
+    /// assert_eq!(derived.dew_point.map(|dew_point| dew_point.celsius().round()), Some(17.0));
+    /// assert!(derived.cloud_base.is_some());
+    /// assert_eq!(data.derived_indoor().dew_point, None);
+    /// ```
+    pub fn derived(&self) -> DerivedWeather {
+        derive(self.temperature(), self.humidity, self.wind_speed(), true)
+    }
+
+    /// Derives the dew point, heat index and more from the indoor readings of this record, `tempinf` and `humidityin`, assuming still air.
+    pub fn derived_indoor(&self) -> DerivedWeather {
+        derive(
+            self.indoor_temperature(),
+            self.humidityin,
+            Some(Speed::default()),
+            false,
+        )
+    }
+}
+
+/// A private function that derives every value it can from a temperature, a relative humidity and a wind speed, which is `None` when the record does not report one.
+fn derive(
+    temperature: Option<Temperature>,
+    humidity: Option<u8>,
+    wind_speed: Option<Speed>,
+    outdoors: bool,
+) -> DerivedWeather {
+    let Some(temperature) = temperature else {
+        return DerivedWeather::default();
+    };
+
+    let outdoor_wind_speed = wind_speed.filter(|_| outdoors);
+    let wind_chill = outdoor_wind_speed.map(|wind_speed| temperature.wind_chill(wind_speed));
+
+    // The dew point, and everything built on it, is undefined in perfectly dry air.
+    let Some(humidity) = humidity.filter(|&humidity| humidity > 0).map(f32::from) else {
+        return DerivedWeather {
+            wind_chill,
+            ..DerivedWeather::default()
+        };
+    };
+
+    let dew_point = temperature.dew_point(humidity);
+
+    DerivedWeather {
+        dew_point: Some(dew_point),
+        heat_index: Some(temperature.heat_index(humidity)),
+        wind_chill,
+        apparent_temperature: wind_speed
+            .map(|wind_speed| temperature.apparent_temperature(humidity, wind_speed)),
+        wet_bulb: Some(temperature.wet_bulb(humidity)),
+        absolute_humidity: Some(temperature.absolute_humidity(humidity)),
+        vapor_pressure: Some(temperature.vapor_pressure(humidity)),
+        cloud_base: outdoors.then(|| temperature.cloud_base(dew_point)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(json: &str) -> WeatherData {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn derives_wind_values_from_reported_wind() {
+        let derived =
+            record(r#"{ "tempf": 20.0, "humidity": 60, "windspeedmph": 15.0 }"#).derived();
+
+        assert_eq!(
+            derived
+                .wind_chill
+                .map(|wind_chill| wind_chill.fahrenheit().round()),
+            Some(6.0)
+        );
+        assert!(derived.apparent_temperature.is_some());
+        assert!(derived.cloud_base.is_some());
+    }
+
+    #[test]
+    fn leaves_out_wind_values_without_wind() {
+        let derived = record(r#"{ "tempf": 20.0, "humidity": 60 }"#).derived();
+
+        assert_eq!(derived.wind_chill, None);
+        assert_eq!(derived.apparent_temperature, None);
+        assert!(derived.dew_point.is_some());
+        assert!(derived.heat_index.is_some());
+        assert!(derived.wet_bulb.is_some());
+    }
+
+    #[test]
+    fn derives_the_cloud_base_without_wind() {
+        let data = record(r#"{ "tempf": 70.0, "humidity": 50 }"#);
+        let derived = data.derived();
+
+        assert_eq!(
+            derived.cloud_base,
+            derived
+                .dew_point
+                .map(|dew_point| data.temperature().unwrap().cloud_base(dew_point))
+        );
+        assert!(derived.cloud_base.is_some());
+    }
+
+    #[test]
+    fn treats_indoor_air_as_still() {
+        let data = record(r#"{ "tempinf": 70.0, "humidityin": 40 }"#);
+        let derived = data.derived_indoor();
+
+        assert_eq!(derived.wind_chill, None);
+        assert_eq!(derived.cloud_base, None);
+        assert_eq!(
+            derived.apparent_temperature,
+            data.indoor_temperature()
+                .map(|temperature| temperature.apparent_temperature(40.0, Speed::default()))
+        );
+    }
+}
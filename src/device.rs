@@ -0,0 +1,256 @@
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::BTreeMap, fmt};
+
+use crate::{json_number, weather_data_struct, AmbientWeatherError, DecodeMode, WeatherData};
+
+/// A weather station registered to an Ambient Weather account, as returned by the `/v1/devices` endpoint.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::Device;
+///
+/// let device: Device = serde_json::from_str(r#"{
+///     "macAddress": "00:0E:C6:20:0F:7B",
+///     "lastData": { "dateutc": 1515436500000, "tempf": 66.9 },
+///     "info": {
+///         "name": "Backyard",
+///         "location": "Home",
+///         "coords": {
+///             "coords": { "lat": 38.9, "lon": -77.0 },
+///             "address": "1600 Pennsylvania Ave NW, Washington, DC",
+///             "location": "Washington",
+///             "elevation": 17.5,
+///             "geo": { "type": "Point", "coordinates": [-77.0, 38.9] }
+///         }
+///     }
+/// }"#).unwrap();
+///
+/// assert_eq!(device.name(), Some("Backyard"));
+/// assert_eq!(device.elevation(), Some(17.5));
+/// assert_eq!(device.last_data.tempf, Some(66.9));
+///
+/// // Fields this crate does not model are kept, and written back out
+/// let json = serde_json::to_value(&device).unwrap();
+/// assert_eq!(json["info"]["coords"]["geo"]["type"], "Point");
+/// ```
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct Device {
+    /// The MAC address that uniquely identifies the device.
+    #[serde(rename = "macAddress")]
+    pub mac_address: String,
+    /// The user supplied name and location of the device.
+    #[serde(default)]
+    pub info: DeviceInfo,
+    /// The most recent weather data reported by the device.
+    #[serde(rename = "lastData", default)]
+    pub last_data: WeatherData,
+    /// Every field of the device this crate does not know about yet, kept as raw JSON.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// The user supplied metadata for a [`Device`].
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DeviceInfo {
+    /// The name given to the device in the Ambient Weather dashboard.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// The free text location given to the device in the Ambient Weather dashboard.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// The geographic position of the device, if one has been set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coords: Option<DeviceLocation>,
+    /// Every field of the device info this crate does not know about yet, kept as raw JSON.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// The geographic position of a [`Device`].
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
+pub struct DeviceLocation {
+    /// The latitude and longitude of the device.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub coords: Option<Coordinates>,
+    /// The street address of the device.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    /// The city or area the device is in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    /// The elevation of the device, in meters.
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub elevation: Option<f64>,
+    /// Every field of the location this crate does not know about yet, such as the GeoJSON `geo` point, kept as raw JSON.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+/// A latitude and longitude pair, in decimal degrees.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq)]
+pub struct Coordinates {
+    /// The latitude, in decimal degrees.
+    #[serde(serialize_with = "json_number::serialize")]
+    pub lat: f64,
+    /// The longitude, in decimal degrees.
+    #[serde(serialize_with = "json_number::serialize")]
+    pub lon: f64,
+}
+
+impl Device {
+    /// Returns a field of the device this crate does not model yet, decoded as `T`. Returns `None` if the field is missing or cannot be decoded as `T`.
+    pub fn extra_field<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.extra
+            .get(name)
+            .and_then(|value| T::deserialize(value).ok())
+    }
+
+    /// Returns the name of the device, if one has been set.
+    pub fn name(&self) -> Option<&str> {
+        self.info.name.as_deref()
+    }
+
+    /// Returns the latitude and longitude of the device, if they have been set.
+    pub fn coordinates(&self) -> Option<Coordinates> {
+        self.info
+            .coords
+            .as_ref()
+            .and_then(|location| location.coords)
+    }
+
+    /// Returns the elevation of the device in meters, if it has been set.
+    pub fn elevation(&self) -> Option<f64> {
+        self.info
+            .coords
+            .as_ref()
+            .and_then(|location| location.elevation)
+    }
+
+    /// Returns the street address of the device, if it has been set.
+    pub fn address(&self) -> Option<&str> {
+        self.info
+            .coords
+            .as_ref()
+            .and_then(|location| location.address.as_deref())
+    }
+}
+
+/// Chooses which device on an account a fetch should use.
+///
+/// Selecting by MAC address or name keeps pointing at the same station when devices are added to, or reordered on, the account, whereas an index does not.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{Device, DeviceSelector};
+///
+/// let devices: Vec<Device> = serde_json::from_str(r#"[
+///     { "macAddress": "00:0E:C6:20:0F:7B", "info": { "name": "Backyard" } },
+///     { "macAddress": "00:0E:C6:20:0F:7C", "info": { "name": "Garage" } }
+/// ]"#).unwrap();
+///
+/// let garage = DeviceSelector::name("Garage").select(&devices).unwrap();
+/// assert_eq!(garage.mac_address, "00:0E:C6:20:0F:7C");
+///
+/// let backyard = DeviceSelector::mac_address("00:0e:c6:20:0f:7b").select(&devices).unwrap();
+/// assert_eq!(backyard.name(), Some("Backyard"));
+///
+/// assert!(DeviceSelector::Index(2).select(&devices).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeviceSelector {
+    /// The position of the device in the account's device list, which for a user with a single station will be 0.
+    Index(usize),
+    /// The MAC address of the device. Matching ignores case.
+    MacAddress(String),
+    /// The name given to the device in the Ambient Weather dashboard. Matching is exact.
+    Name(String),
+}
+
+impl DeviceSelector {
+    /// Creates a selector matching a device by MAC address.
+    pub fn mac_address(mac_address: impl Into<String>) -> Self {
+        DeviceSelector::MacAddress(mac_address.into())
+    }
+
+    /// Creates a selector matching a device by its dashboard name.
+    pub fn name(name: impl Into<String>) -> Self {
+        DeviceSelector::Name(name.into())
+    }
+
+    /// Returns the position of the first device in `devices` that this selector matches.
+    pub fn position(&self, devices: &[Device]) -> Option<usize> {
+        match self {
+            DeviceSelector::Index(index) => (*index < devices.len()).then_some(*index),
+            DeviceSelector::MacAddress(mac_address) => devices
+                .iter()
+                .position(|device| device.mac_address.eq_ignore_ascii_case(mac_address)),
+            DeviceSelector::Name(name) => devices
+                .iter()
+                .position(|device| device.name() == Some(name.as_str())),
+        }
+    }
+
+    /// Returns the first device in `devices` that this selector matches.
+    pub fn select<'a>(&self, devices: &'a [Device]) -> Option<&'a Device> {
+        self.position(devices).map(|index| &devices[index])
+    }
+}
+
+impl Default for DeviceSelector {
+    fn default() -> Self {
+        DeviceSelector::Index(0)
+    }
+}
+
+impl From<usize> for DeviceSelector {
+    fn from(index: usize) -> Self {
+        DeviceSelector::Index(index)
+    }
+}
+
+impl fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeviceSelector::Index(index) => write!(f, "index {index}"),
+            DeviceSelector::MacAddress(mac_address) => write!(f, "MAC address {mac_address}"),
+            DeviceSelector::Name(name) => write!(f, "name \"{name}\""),
+        }
+    }
+}
+
+/// A private function for decoding a single device. The embedded `lastData` record is decoded as strictly as `mode` asks, and when it is what failed, the error names the offending field within it.
+pub(crate) fn decode_device(
+    value: &Value,
+    mode: DecodeMode,
+) -> Result<Device, AmbientWeatherError> {
+    let mut value = value.clone();
+    let last_data = value
+        .as_object_mut()
+        .and_then(|device| device.remove("lastData"));
+
+    let mut device =
+        Device::deserialize(&value).map_err(|err| AmbientWeatherError::decode(None, err))?;
+
+    if let Some(last_data) = last_data {
+        device.last_data = weather_data_struct::decode_weather_data(&last_data, mode).map_err(
+            |err| match err {
+                AmbientWeatherError::Decode { field, source } => AmbientWeatherError::Decode {
+                    field: Some(match field {
+                        Some(field) => format!("lastData.{field}"),
+                        None => "lastData".to_string(),
+                    }),
+                    source,
+                },
+                err => err,
+            },
+        )?;
+    }
+
+    Ok(device)
+}
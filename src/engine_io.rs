@@ -0,0 +1,397 @@
+use reqwest::header::{CONTENT_TYPE, COOKIE, SET_COOKIE};
+use serde::Deserialize;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::AmbientWeatherError;
+
+/// A private Engine.IO packet, as carried by the long-polling transport.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Packet {
+    /// Sent by the server once a session has been created.
+    Open(OpenPayload),
+    /// Either side is closing the session.
+    Close,
+    /// A heartbeat, with an optional probe payload.
+    Ping(String),
+    /// The answer to a heartbeat.
+    Pong(String),
+    /// A message for the layer above, which is Socket.IO here.
+    Message(String),
+    /// A binary message for the layer above, which the server sends base64 encoded as the session asks it to.
+    Binary(Vec<u8>),
+    /// A request to switch transports, which the polling transport never makes.
+    Upgrade,
+    /// A packet that carries nothing.
+    Noop,
+}
+
+/// A private representation of the handshake data the server sends in its `open` packet.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub(crate) struct OpenPayload {
+    /// The session id every later request must carry.
+    pub(crate) sid: String,
+    /// How often, in milliseconds, a heartbeat is expected.
+    #[serde(rename = "pingInterval")]
+    pub(crate) ping_interval: u64,
+    /// How long, in milliseconds, to wait for a heartbeat before giving up on the session.
+    #[serde(rename = "pingTimeout")]
+    pub(crate) ping_timeout: u64,
+}
+
+impl Packet {
+    /// A private function that decodes a single packet from its text form.
+    fn decode(text: &str) -> Result<Packet, AmbientWeatherError> {
+        let mut chars = text.chars();
+        let packet_type = chars.next();
+        let data = chars.as_str();
+
+        match packet_type {
+            Some('0') => serde_json::from_str(data)
+                .map(Packet::Open)
+                .map_err(|err| AmbientWeatherError::decode(Some("open".to_string()), err)),
+            Some('1') => Ok(Packet::Close),
+            Some('2') => Ok(Packet::Ping(data.to_string())),
+            Some('3') => Ok(Packet::Pong(data.to_string())),
+            Some('4') => Ok(Packet::Message(data.to_string())),
+            Some('5') => Ok(Packet::Upgrade),
+            Some('6') => Ok(Packet::Noop),
+            Some('b') => match data.strip_prefix('4') {
+                Some(data) => Packet::decode_binary(data),
+                None => Err(AmbientWeatherError::Realtime(format!(
+                    "unknown Engine.IO packet `{text}`"
+                ))),
+            },
+            _ => Err(AmbientWeatherError::Realtime(format!(
+                "unknown Engine.IO packet `{text}`"
+            ))),
+        }
+    }
+
+    /// A private function that decodes the base64 data of a binary message.
+    fn decode_binary(data: &str) -> Result<Packet, AmbientWeatherError> {
+        decode_base64(data).map(Packet::Binary).ok_or_else(|| {
+            AmbientWeatherError::Realtime(format!("malformed base64 in Engine.IO packet `{data}`"))
+        })
+    }
+
+    /// A private function that encodes a single packet into its text form.
+    fn encode(&self) -> String {
+        match self {
+            Packet::Open(_) => "0".to_string(),
+            Packet::Close => "1".to_string(),
+            Packet::Ping(data) => format!("2{data}"),
+            Packet::Pong(data) => format!("3{data}"),
+            Packet::Message(data) => format!("4{data}"),
+            Packet::Binary(data) => format!("b4{}", encode_base64(data)),
+            Packet::Upgrade => "5".to_string(),
+            Packet::Noop => "6".to_string(),
+        }
+    }
+}
+
+/// A private function that splits a polling payload into packets.
+///
+/// Engine.IO 3 prefixes each packet with its length in UTF-16 code units and a colon (`2:40`), while Engine.IO 4 separates packets with a record separator character. Both are accepted, along with the base64 binary messages of each, `b4` followed by the data in Engine.IO 3 and `b` in Engine.IO 4.
+pub(crate) fn decode_payload(payload: &str) -> Result<Vec<Packet>, AmbientWeatherError> {
+    if !starts_with_length_prefix(payload) {
+        return payload
+            .split('\u{1e}')
+            .filter(|packet| !packet.is_empty())
+            .map(|packet| match packet.strip_prefix('b') {
+                Some(data) => Packet::decode_binary(data),
+                None => Packet::decode(packet),
+            })
+            .collect();
+    }
+
+    let mut packets = Vec::new();
+    let mut rest = payload;
+
+    while !rest.is_empty() {
+        let malformed =
+            || AmbientWeatherError::Realtime(format!("malformed Engine.IO payload `{payload}`"));
+
+        let (length, after_length) = rest.split_once(':').ok_or_else(malformed)?;
+        let length: usize = length.parse().map_err(|_| malformed())?;
+
+        let mut units = 0;
+        let mut end = after_length.len();
+        for (index, character) in after_length.char_indices() {
+            if units == length {
+                end = index;
+                break;
+            }
+            units += character.len_utf16();
+        }
+        if units < length {
+            return Err(malformed());
+        }
+
+        packets.push(Packet::decode(&after_length[..end])?);
+        rest = &after_length[end..];
+    }
+
+    Ok(packets)
+}
+
+/// A private function that joins packets into an Engine.IO 3 polling payload.
+pub(crate) fn encode_payload(packets: &[Packet]) -> String {
+    packets
+        .iter()
+        .map(|packet| {
+            let text = packet.encode();
+            format!("{}:{text}", text.encode_utf16().count())
+        })
+        .collect()
+}
+
+/// A private function that checks whether a payload uses Engine.IO 3 length prefixes.
+fn starts_with_length_prefix(payload: &str) -> bool {
+    let digits = payload.bytes().take_while(u8::is_ascii_digit).count();
+
+    digits > 0 && payload.as_bytes().get(digits) == Some(&b':')
+}
+
+/// The characters of the standard base64 alphabet, in order of the value they encode.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A private function that decodes standard base64, with or without padding. Returns `None` if the text is not base64.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text
+        .strip_suffix("==")
+        .or_else(|| text.strip_suffix('='))
+        .unwrap_or(text);
+    if text.len() % 4 == 1 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(text.len() * 3 / 4);
+    let mut bits = 0u32;
+    let mut bit_count = 0;
+
+    for character in text.bytes() {
+        let value = BASE64_ALPHABET
+            .iter()
+            .position(|&letter| letter == character)?;
+        bits = bits << 6 | value as u32;
+        bit_count += 6;
+
+        if bit_count >= 8 {
+            bit_count -= 8;
+            bytes.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+
+    Some(bytes)
+}
+
+/// A private function that encodes bytes as standard base64, with padding.
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0u32, |bits, (index, &byte)| {
+            bits | u32::from(byte) << (16 - 8 * index)
+        });
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                text.push(BASE64_ALPHABET[(bits >> (18 - 6 * index) & 63) as usize] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+
+    text
+}
+
+/// A private Engine.IO session over the HTTP long-polling transport.
+pub(crate) struct PollingSession {
+    http: reqwest::Client,
+    url: String,
+    query: Vec<(String, String)>,
+    cookies: Option<String>,
+    open: OpenPayload,
+}
+
+impl PollingSession {
+    /// Opens a new session with the server at `url`, sending `query` along with every request. Returns the session and any packets that arrived with the handshake.
+    pub(crate) async fn connect(
+        http: reqwest::Client,
+        url: String,
+        mut query: Vec<(String, String)>,
+    ) -> Result<(Self, Vec<Packet>), AmbientWeatherError> {
+        query.extend([
+            ("EIO".to_string(), "3".to_string()),
+            ("transport".to_string(), "polling".to_string()),
+            ("b64".to_string(), "1".to_string()),
+        ]);
+
+        let response = http
+            .get(&url)
+            .query(&query)
+            .query(&[("t", cache_buster())])
+            .send()
+            .await?
+            .error_for_status()?;
+
+        // Load balancers in front of Socket.IO servers often rely on sticky cookies to route every request of a session to the same server.
+        let cookies: Vec<&str> = response
+            .headers()
+            .get_all(SET_COOKIE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| value.split(';').next())
+            .collect();
+        let cookies = (!cookies.is_empty()).then(|| cookies.join("; "));
+
+        let mut packets = decode_payload(&response.text().await?)?.into_iter();
+
+        let open = match packets.next() {
+            Some(Packet::Open(open)) => open,
+            _ => {
+                return Err(AmbientWeatherError::Realtime(
+                    "the server did not open an Engine.IO session".to_string(),
+                ))
+            }
+        };
+
+        query.push(("sid".to_string(), open.sid.clone()));
+
+        let session = PollingSession {
+            http,
+            url,
+            query,
+            cookies,
+            open,
+        };
+
+        Ok((session, packets.collect()))
+    }
+
+    /// Returns the handshake data the server sent when the session was opened.
+    pub(crate) fn open(&self) -> &OpenPayload {
+        &self.open
+    }
+
+    /// Waits for the server to send the next batch of packets.
+    pub(crate) async fn poll(&self) -> Result<Vec<Packet>, AmbientWeatherError> {
+        let mut request = self
+            .http
+            .get(&self.url)
+            .query(&self.query)
+            .query(&[("t", cache_buster())]);
+        if let Some(cookies) = &self.cookies {
+            request = request.header(COOKIE, cookies);
+        }
+
+        let response = request.send().await?.error_for_status()?;
+
+        decode_payload(&response.text().await?)
+    }
+
+    /// Sends a batch of packets to the server.
+    pub(crate) async fn send(&self, packets: &[Packet]) -> Result<(), AmbientWeatherError> {
+        let mut request = self
+            .http
+            .post(&self.url)
+            .query(&self.query)
+            .query(&[("t", cache_buster())])
+            .header(CONTENT_TYPE, "text/plain;charset=UTF-8")
+            .body(encode_payload(packets));
+        if let Some(cookies) = &self.cookies {
+            request = request.header(COOKIE, cookies);
+        }
+
+        request.send().await?.error_for_status()?;
+
+        Ok(())
+    }
+}
+
+/// A private function that returns a unique value for the `t` query parameter, so that no proxy serves a cached poll.
+fn cache_buster() -> String {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_several_packets_of_one_payload() {
+        let packets = decode_payload("1:62:3x13:42[\"data\",{}]").unwrap();
+
+        assert_eq!(
+            packets,
+            [
+                Packet::Noop,
+                Packet::Pong("x".to_string()),
+                Packet::Message("2[\"data\",{}]".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_lengths_in_utf_16_units() {
+        // `é` is one UTF-16 unit but two bytes, and `🌧` is two UTF-16 units but four bytes
+        let payload = "5:4\"é°\"4:4🌧x2:2é";
+
+        assert_eq!(
+            decode_payload(payload).unwrap(),
+            [
+                Packet::Message("\"é°\"".to_string()),
+                Packet::Message("🌧x".to_string()),
+                Packet::Ping("é".to_string()),
+            ]
+        );
+        assert_eq!(encode_payload(&decode_payload(payload).unwrap()), payload);
+    }
+
+    #[test]
+    fn rejects_lengths_that_overrun_the_payload() {
+        assert!(decode_payload("5:4🌧x").is_err());
+        assert!(decode_payload("3:4").is_err());
+    }
+
+    #[test]
+    fn decodes_base64_binary_messages() {
+        let packets = decode_payload("6:b4AQID2:40").unwrap();
+
+        assert_eq!(
+            packets,
+            [
+                Packet::Binary(vec![1, 2, 3]),
+                Packet::Message("0".to_string())
+            ]
+        );
+        assert_eq!(
+            decode_payload("bAQ==\u{1e}bAQI=\u{1e}40").unwrap(),
+            [
+                Packet::Binary(vec![1]),
+                Packet::Binary(vec![1, 2]),
+                Packet::Message("0".to_string()),
+            ]
+        );
+        assert!(decode_payload("4:b4A!").is_err());
+    }
+
+    #[test]
+    fn round_trips_binary_messages() {
+        for length in 0..8u8 {
+            let packet = Packet::Binary((0..length).map(|byte| byte * 37).collect());
+
+            assert_eq!(
+                decode_payload(&encode_payload(std::slice::from_ref(&packet))).unwrap(),
+                [packet]
+            );
+        }
+    }
+}
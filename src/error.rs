@@ -0,0 +1,110 @@
+use std::{fmt, time::Duration};
+
+use crate::DeviceSelector;
+
+/// The error type returned by every fallible function in this crate.
+#[derive(Debug)]
+pub enum AmbientWeatherError {
+    /// The request could not be sent, or the Ambient Weather API answered with an unexpected HTTP status.
+    Http(reqwest::Error),
+    /// The Ambient Weather API answered with `429 Too Many Requests`. `retry_after` holds the `Retry-After` header when one was sent.
+    RateLimited {
+        /// How long the API asked us to wait before trying again, if it said so.
+        retry_after: Option<Duration>,
+    },
+    /// The Ambient Weather API rejected the API key or the Application key.
+    Unauthorized,
+    /// No device on the account matched the requested selector.
+    DeviceNotFound(DeviceSelector),
+    /// The response could not be decoded. `field` names the JSON field that failed, when it could be determined.
+    Decode {
+        /// The JSON field that could not be decoded, if known.
+        field: Option<String>,
+        /// The underlying serde error.
+        source: serde_json::Error,
+    },
+    /// A request kept failing after being retried as many times as the [`RetryPolicy`](crate::RetryPolicy) allows.
+    RetriesExhausted {
+        /// The number of attempts made, including the first one.
+        attempts: u32,
+        /// The error the final attempt failed with.
+        last_error: Box<AmbientWeatherError>,
+    },
+    /// The realtime connection broke down, or the server sent something that does not follow the Socket.IO protocol.
+    Realtime(String),
+    /// The Tokio runtime used by the blocking functions could not be started.
+    Runtime(std::io::Error),
+}
+
+impl AmbientWeatherError {
+    /// Returns the error that ultimately caused this one, looking through [`AmbientWeatherError::RetriesExhausted`].
+    pub fn last_error(&self) -> &AmbientWeatherError {
+        match self {
+            AmbientWeatherError::RetriesExhausted { last_error, .. } => last_error.last_error(),
+            err => err,
+        }
+    }
+
+    /// A private helper for building a `Decode` error with field context.
+    pub(crate) fn decode(field: Option<String>, source: serde_json::Error) -> Self {
+        AmbientWeatherError::Decode { field, source }
+    }
+}
+
+impl fmt::Display for AmbientWeatherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AmbientWeatherError::Http(err) => write!(f, "HTTP error: {err}"),
+            AmbientWeatherError::RateLimited {
+                retry_after: Some(wait),
+            } => write!(
+                f,
+                "rate limited by the Ambient Weather API, retry after {}s",
+                wait.as_secs()
+            ),
+            AmbientWeatherError::RateLimited { retry_after: None } => {
+                write!(f, "rate limited by the Ambient Weather API")
+            }
+            AmbientWeatherError::Unauthorized => {
+                write!(f, "the API key or Application key was rejected")
+            }
+            AmbientWeatherError::DeviceNotFound(selector) => {
+                write!(f, "no device found matching {selector}")
+            }
+            AmbientWeatherError::Decode {
+                field: Some(field),
+                source,
+            } => write!(f, "failed to decode field `{field}`: {source}"),
+            AmbientWeatherError::Decode {
+                field: None,
+                source,
+            } => write!(f, "failed to decode response: {source}"),
+            AmbientWeatherError::RetriesExhausted {
+                attempts,
+                last_error,
+            } => write!(f, "request failed after {attempts} attempts: {last_error}"),
+            AmbientWeatherError::Realtime(message) => write!(f, "realtime error: {message}"),
+            AmbientWeatherError::Runtime(err) => {
+                write!(f, "failed to start the Tokio runtime: {err}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AmbientWeatherError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AmbientWeatherError::Http(err) => Some(err),
+            AmbientWeatherError::Decode { source, .. } => Some(source),
+            AmbientWeatherError::RetriesExhausted { last_error, .. } => Some(last_error.as_ref()),
+            AmbientWeatherError::Runtime(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for AmbientWeatherError {
+    fn from(err: reqwest::Error) -> Self {
+        AmbientWeatherError::Http(err)
+    }
+}
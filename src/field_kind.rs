@@ -0,0 +1,242 @@
+use serde::{
+    de::{
+        self, DeserializeOwned, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, Visitor,
+    },
+    forward_to_deserialize_any,
+};
+use serde_json::Value;
+use std::fmt;
+
+/// The type a field of a record is decoded as, found by asking the field's own `Deserialize` implementation what it expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FieldKind {
+    /// A floating point number.
+    Float,
+    /// A whole number from 0 up to `max`.
+    Unsigned { max: u64 },
+    /// Any other type, such as a date or a time zone name.
+    Other,
+}
+
+impl FieldKind {
+    /// Returns the kind of the field named `key` in records of type `T`, or `None` if `T` does not know the field. Fields `T` sets aside for a flattened field are looked up in `U`, the type of that field.
+    pub(crate) fn of<T: DeserializeOwned, U: DeserializeOwned>(key: &str) -> Option<FieldKind> {
+        match probe::<T>(key) {
+            Probe::Found(kind) => Some(kind),
+            Probe::Untyped => match probe::<U>(key) {
+                Probe::Found(kind) => Some(kind),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Turns a value into one that fits the field: numbers are rounded for whole number fields, numeric strings are parsed, and anything else, including numbers out of range, becomes `null`. Values of other kinds are returned as they are.
+    pub(crate) fn repair(self, value: &Value) -> Value {
+        let number = match value {
+            Value::Number(number) => number.as_f64(),
+            Value::String(text) => text.trim().parse::<f64>().ok(),
+            _ => None,
+        }
+        .filter(|number| number.is_finite());
+
+        match (self, number) {
+            (FieldKind::Other, _) => value.clone(),
+            (FieldKind::Float, Some(number)) => match value {
+                Value::Number(_) => value.clone(),
+                _ => serde_json::Number::from_f64(number).map_or(Value::Null, Value::Number),
+            },
+            (FieldKind::Unsigned { max }, Some(number)) => {
+                let rounded = number.round();
+
+                if (0.0..=max as f64).contains(&rounded) {
+                    Value::from(rounded as u64)
+                } else {
+                    Value::Null
+                }
+            }
+            (_, None) => Value::Null,
+        }
+    }
+}
+
+/// A private function that finds out what type `T` decodes the field named `key` as, by decoding a record holding only that field and stopping at its value.
+fn probe<T: DeserializeOwned>(key: &str) -> Probe {
+    match T::deserialize(ProbeRecord { key }) {
+        Ok(_) => Probe::Unknown,
+        Err(probe) => probe,
+    }
+}
+
+/// A private type for the outcome of a probe, carried as the error that stops the decoding.
+#[derive(Debug)]
+enum Probe {
+    /// The field is decoded as the given kind.
+    Found(FieldKind),
+    /// The field is not one of the record's own and was set aside for a flattened field.
+    Untyped,
+    /// The field is ignored, or the record failed for some other reason.
+    Unknown,
+}
+
+impl fmt::Display for Probe {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+impl std::error::Error for Probe {}
+
+impl de::Error for Probe {
+    fn custom<T: fmt::Display>(_: T) -> Self {
+        Probe::Unknown
+    }
+}
+
+/// A private deserializer for a record made of the single field `key`.
+struct ProbeRecord<'a> {
+    key: &'a str,
+}
+
+impl<'de> Deserializer<'de> for ProbeRecord<'_> {
+    type Error = Probe;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Probe> {
+        visitor.visit_map(ProbeFields {
+            key: Some(self.key),
+        })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// A private map access that yields the field `key` once, with a value that reports the type it is asked for.
+struct ProbeFields<'a> {
+    key: Option<&'a str>,
+}
+
+impl<'de> MapAccess<'de> for ProbeFields<'_> {
+    type Error = Probe;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Probe> {
+        self.key
+            .take()
+            .map(|key| seed.deserialize(key.into_deserializer()))
+            .transpose()
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Probe> {
+        seed.deserialize(ProbeValue { optional: false })
+    }
+}
+
+/// A private deserializer for the value of the probed field, which stops decoding with the type asked of it. A field read as an `Option` is the record's own, while one read without a type hint was set aside for a flattened field.
+struct ProbeValue {
+    optional: bool,
+}
+
+impl<'de> Deserializer<'de> for ProbeValue {
+    type Error = Probe;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(match self.optional {
+            true => Probe::Found(FieldKind::Other),
+            false => Probe::Untyped,
+        })
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Probe> {
+        visitor.visit_some(ProbeValue { optional: true })
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(Probe::Found(FieldKind::Float))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(Probe::Found(FieldKind::Float))
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(unsigned(u8::MAX.into()))
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(unsigned(u16::MAX.into()))
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(unsigned(u32::MAX.into()))
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(unsigned(u64::MAX))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Probe> {
+        Err(Probe::Unknown)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u128 char str string bytes byte_buf unit
+        unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+    }
+}
+
+/// A private function that reports a whole number field holding up to `max`.
+fn unsigned(max: u64) -> Probe {
+    Probe::Found(FieldKind::Unsigned { max })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SensorChannels, WeatherData};
+
+    fn kind(key: &str) -> Option<FieldKind> {
+        FieldKind::of::<WeatherData, SensorChannels>(key)
+    }
+
+    #[test]
+    fn finds_the_type_of_each_field() {
+        assert_eq!(kind("tempf"), Some(FieldKind::Float));
+        assert_eq!(kind("humidity"), Some(FieldKind::Unsigned { max: 255 }));
+        assert_eq!(kind("winddir"), Some(FieldKind::Unsigned { max: 65535 }));
+        assert_eq!(kind("dateutc"), Some(FieldKind::Other));
+        assert_eq!(kind("tz"), Some(FieldKind::Other));
+    }
+
+    #[test]
+    fn finds_the_type_of_numbered_fields() {
+        assert_eq!(kind("temp3f"), Some(FieldKind::Float));
+        assert_eq!(kind("batleak10"), Some(FieldKind::Unsigned { max: 255 }));
+        assert_eq!(kind("temp11f"), None);
+        assert_eq!(kind("someNewField"), None);
+    }
+
+    #[test]
+    fn repairs_values_to_fit_their_kind() {
+        let byte = FieldKind::Unsigned { max: 255 };
+
+        assert_eq!(byte.repair(&serde_json::json!(45.4)), 45);
+        assert_eq!(byte.repair(&serde_json::json!(" 45 ")), 45);
+        assert_eq!(byte.repair(&serde_json::json!(256)), Value::Null);
+        assert_eq!(byte.repair(&serde_json::json!(-1)), Value::Null);
+        assert_eq!(byte.repair(&serde_json::json!("N/A")), Value::Null);
+        assert_eq!(byte.repair(&serde_json::json!(true)), Value::Null);
+
+        assert_eq!(FieldKind::Float.repair(&serde_json::json!("71.5")), 71.5);
+        assert_eq!(FieldKind::Float.repair(&serde_json::json!(71)), 71);
+        assert_eq!(FieldKind::Float.repair(&serde_json::json!([])), Value::Null);
+
+        let date = serde_json::json!("2023-10-15");
+        assert_eq!(FieldKind::Other.repair(&date), date);
+    }
+}
@@ -0,0 +1,395 @@
+use futures_core::Stream;
+use serde_json::Value;
+use std::{
+    collections::HashSet,
+    future::Future,
+    ops::Deref,
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{AmbientWeatherClient, AmbientWeatherError, WeatherData};
+
+/// The largest number of records the Ambient Weather API will return in a single historic request.
+pub const MAX_HISTORIC_LIMIT: u16 = 288;
+
+/// The parameters for a historic data request.
+///
+/// By default the Ambient Weather API returns the most recent page of records. Setting an end date asks for the records leading up to that time instead, and setting a limit caps how many come back.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::HistoricQuery;
+/// use std::time::{Duration, SystemTime};
+///
+/// let yesterday = SystemTime::now() - Duration::from_secs(24 * 60 * 60);
+///
+/// let query = HistoricQuery::new().end_date(yesterday).limit(500);
+/// assert_eq!(query.get_limit(), Some(288));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HistoricQuery {
+    end_date: Option<SystemTime>,
+    limit: Option<u16>,
+}
+
+impl HistoricQuery {
+    /// Creates a query for the most recent page of records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Only returns records from before `end_date`.
+    pub fn end_date(mut self, end_date: SystemTime) -> Self {
+        self.end_date = Some(end_date);
+        self
+    }
+
+    /// Returns at most `limit` records. Values above [`MAX_HISTORIC_LIMIT`] are capped to it.
+    pub fn limit(mut self, limit: u16) -> Self {
+        self.limit = Some(limit.min(MAX_HISTORIC_LIMIT));
+        self
+    }
+
+    /// Returns the end date of this query, if one has been set.
+    pub fn get_end_date(&self) -> Option<SystemTime> {
+        self.end_date
+    }
+
+    /// Returns the record limit of this query, if one has been set.
+    pub fn get_limit(&self) -> Option<u16> {
+        self.limit
+    }
+
+    /// A private function that turns the query into the parameters the Ambient Weather API expects.
+    pub(crate) fn query_pairs(&self) -> Vec<(&'static str, String)> {
+        let mut pairs = Vec::new();
+
+        if let Some(end_date) = self.end_date {
+            pairs.push(("endDate", epoch_millis(end_date).to_string()));
+        }
+        if let Some(limit) = self.limit {
+            pairs.push(("limit", limit.to_string()));
+        }
+
+        pairs
+    }
+}
+
+/// The records of a historic data request, along with any records that could not be decoded.
+///
+/// A record that fails to decode does not fail the whole request. It is reported in `errors` instead, and every other record is still returned. With the default [`DecodeMode::Lenient`](crate::DecodeMode::Lenient) this only happens for records that are not JSON objects at all.
+///
+/// `HistoricRecords` dereferences to a slice of the decoded records, so it can be iterated and indexed like the `Vec<WeatherData>` it wraps.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ambient_weather_api::*;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let api_credentials = AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     };
+///
+///     let historic_data = get_historic_device_data(&api_credentials)?;
+///     for record_error in &historic_data.errors {
+///         println!("Skipped record {}: {}", record_error.index, record_error.error);
+///     }
+///     println!("Decoded {} records", historic_data.len());
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct HistoricRecords {
+    /// The records that were decoded, newest first.
+    pub records: Vec<WeatherData>,
+    /// The records that could not be decoded.
+    pub errors: Vec<RecordError>,
+}
+
+/// A historic record that could not be decoded, reported in [`HistoricRecords::errors`].
+#[derive(Debug)]
+pub struct RecordError {
+    /// The page of a [`HistoricPages`] walk the record came in, counting from 0. Always 0 for a single request.
+    pub page: usize,
+    /// The position of the record in the API's response for its page.
+    pub index: usize,
+    /// The record as the API returned it.
+    pub record: Value,
+    /// Why the record could not be decoded.
+    pub error: AmbientWeatherError,
+}
+
+impl HistoricRecords {
+    /// Returns `true` if every record was decoded.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the decoded records, dropping the errors.
+    pub fn into_records(self) -> Vec<WeatherData> {
+        self.records
+    }
+
+    /// A private function that decodes every record of an API response.
+    pub(crate) fn decode(values: Vec<Value>, mode: crate::DecodeMode) -> Self {
+        let mut batch = HistoricRecords::default();
+
+        for (index, record) in values.into_iter().enumerate() {
+            match crate::weather_data_struct::decode_weather_data(&record, mode) {
+                Ok(data) => batch.records.push(data),
+                Err(error) => batch.errors.push(RecordError {
+                    page: 0,
+                    index,
+                    record,
+                    error,
+                }),
+            }
+        }
+
+        batch
+    }
+}
+
+impl Deref for HistoricRecords {
+    type Target = [WeatherData];
+
+    fn deref(&self) -> &[WeatherData] {
+        &self.records
+    }
+}
+
+impl IntoIterator for HistoricRecords {
+    type Item = WeatherData;
+    type IntoIter = std::vec::IntoIter<WeatherData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a HistoricRecords {
+    type Item = &'a WeatherData;
+    type IntoIter = std::slice::Iter<'a, WeatherData>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.records.iter()
+    }
+}
+
+/// Walks backward through a device's history one page at a time, created by [`AmbientWeatherClient::historic_pages`].
+///
+/// Each page is newest record first. Records older than the start time are dropped, records already returned by an earlier page are skipped based on their `dateutc`, and the walk ends once the start time is reached or the API has nothing older to give.
+///
+/// The pager is a [`Stream`] of pages, and also offers [`HistoricPages::next_page`] for use without a stream combinator library.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ambient_weather_api::*;
+/// use std::time::{Duration, SystemTime};
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let client = AmbientWeatherClient::new(AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     });
+///
+///     // Walk back through the last three days of records
+///     let start = SystemTime::now() - Duration::from_secs(3 * 24 * 60 * 60);
+///     let mut pages = client
+///         .historic_pages(&DeviceSelector::Index(0), HistoricQuery::new(), start)
+///         .await?;
+///
+///     while let Some(page) = pages.next_page().await {
+///         for data in page? {
+///             println!("The historic temp was: {}F", data.tempf.unwrap_or_default());
+///         }
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub struct HistoricPages {
+    mac_address: String,
+    walk: Option<PageWalk>,
+    pending: Option<PendingPage>,
+}
+
+/// A private alias for a page request in flight, which hands the walk back along with the page.
+type PendingPage = Pin<Box<dyn Future<Output = (PageWalk, Option<PageResult>)> + Send>>;
+
+/// A private alias for the result of fetching a page.
+type PageResult = Result<HistoricRecords, AmbientWeatherError>;
+
+/// The private state of a walk through a device's history, between page requests.
+struct PageWalk {
+    client: AmbientWeatherClient,
+    mac_address: String,
+    query: HistoricQuery,
+    start: i64,
+    seen: HashSet<i64>,
+    pages: usize,
+    finished: bool,
+}
+
+impl HistoricPages {
+    /// A private function for creating a pager for an already resolved device.
+    pub(crate) fn new(
+        client: AmbientWeatherClient,
+        mac_address: String,
+        query: HistoricQuery,
+        start: SystemTime,
+    ) -> Self {
+        HistoricPages {
+            mac_address: mac_address.clone(),
+            walk: Some(PageWalk {
+                client,
+                mac_address,
+                query,
+                start: epoch_millis(start),
+                seen: HashSet::new(),
+                pages: 0,
+                finished: false,
+            }),
+            pending: None,
+        }
+    }
+
+    /// Returns the MAC address of the device being paged through.
+    pub fn mac_address(&self) -> &str {
+        &self.mac_address
+    }
+
+    /// Fetches the next, older, page of records. Returns `None` once the start time has been reached or the history is exhausted.
+    ///
+    /// After an error is returned the pager is finished, and further calls return `None`. Records that cannot be decoded do not end the walk, and are reported in [`HistoricRecords::errors`].
+    pub async fn next_page(&mut self) -> Option<Result<HistoricRecords, AmbientWeatherError>> {
+        std::future::poll_fn(|cx| Pin::new(&mut *self).poll_next(cx)).await
+    }
+
+    /// Fetches every remaining page and returns all of their records, newest first, along with every record that could not be decoded. Each error keeps the page it came in, see [`RecordError::page`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error any page request runs into.
+    pub async fn collect_all(mut self) -> Result<HistoricRecords, AmbientWeatherError> {
+        let mut all = HistoricRecords::default();
+
+        while let Some(page) = self.next_page().await {
+            let page = page?;
+            all.records.extend(page.records);
+            all.errors.extend(page.errors);
+        }
+
+        Ok(all)
+    }
+}
+
+impl Stream for HistoricPages {
+    type Item = Result<HistoricRecords, AmbientWeatherError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.pending.is_none() {
+            let Some(mut walk) = self.walk.take() else {
+                return Poll::Ready(None);
+            };
+            self.pending = Some(Box::pin(async move {
+                let page = walk.next_page().await;
+                (walk, page)
+            }));
+        }
+
+        let pending = self.pending.as_mut().expect("a page is pending");
+        let (walk, page) = std::task::ready!(pending.as_mut().poll(cx));
+        self.pending = None;
+        self.walk = Some(walk);
+
+        Poll::Ready(page)
+    }
+}
+
+impl PageWalk {
+    /// A private function that fetches pages until one has records or errors to return, or the walk ends.
+    async fn next_page(&mut self) -> Option<Result<HistoricRecords, AmbientWeatherError>> {
+        while !self.finished {
+            let page = match self
+                .client
+                .historic_page(&self.mac_address, &self.query)
+                .await
+            {
+                Ok(page) => page,
+                Err(err) => {
+                    self.finished = true;
+                    return Some(Err(err));
+                }
+            };
+
+            let HistoricRecords {
+                records: page,
+                mut errors,
+            } = page;
+            for error in &mut errors {
+                error.page = self.pages;
+            }
+            self.pages += 1;
+
+            let oldest = page
+                .iter()
+                .filter_map(|data| data.dateutc.map(|dateutc| dateutc.as_millis()))
+                .min();
+
+            let records: Vec<WeatherData> = page
+                .into_iter()
+                .filter(
+                    |data| match data.dateutc.map(|dateutc| dateutc.as_millis()) {
+                        Some(dateutc) => dateutc >= self.start && self.seen.insert(dateutc),
+                        None => false,
+                    },
+                )
+                .collect();
+
+            // A page can hold nothing but records at the end date itself, which the API includes, so the next page starts just before it. A page older than the end date means the API ignored it, and the walk would never end.
+            let end_date = self.query.end_date.map(epoch_millis);
+            let next_end_date = match (oldest, end_date) {
+                (Some(oldest), Some(end_date)) if oldest == end_date => Some(oldest - 1),
+                (Some(oldest), Some(end_date)) if oldest > end_date => None,
+                (oldest, _) => oldest,
+            };
+
+            match next_end_date {
+                Some(next_end_date) if next_end_date >= self.start => {
+                    self.query.end_date =
+                        Some(UNIX_EPOCH + Duration::from_millis(next_end_date.max(0) as u64));
+                }
+                _ => self.finished = true,
+            }
+
+            if !records.is_empty() || !errors.is_empty() {
+                return Some(Ok(HistoricRecords { records, errors }));
+            }
+        }
+
+        None
+    }
+}
+
+/// A private function that converts a point in time into milliseconds since the Unix epoch, the unit Ambient Weather uses for `dateutc`.
+pub(crate) fn epoch_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(since_epoch) => since_epoch.as_millis() as i64,
+        Err(before_epoch) => -(before_epoch.duration().as_millis() as i64),
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Serialize, Serializer};
+
+/// The largest whole number a JSON number written by JavaScript holds exactly, 2^53.
+const MAX_SAFE_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+/// A number serialized the way Ambient Weather writes it. The API is written in JavaScript, which writes whole numbers without a fraction, so a reading of `70.0` is written as `70` to reproduce the API JSON exactly.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct JsonNumber<T>(pub(crate) T);
+
+impl<T: Copy + Into<f64> + Serialize> Serialize for JsonNumber<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let number: f64 = self.0.into();
+
+        if number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER {
+            serializer.serialize_i64(number as i64)
+        } else {
+            self.0.serialize(serializer)
+        }
+    }
+}
+
+/// Serializes a number as a [`JsonNumber`], for use with `#[serde(serialize_with)]`.
+pub(crate) fn serialize<S: Serializer, T: Copy + Into<f64> + Serialize>(
+    value: &T,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    JsonNumber(*value).serialize(serializer)
+}
+
+/// Serializes an optional number as a [`JsonNumber`], for use with `#[serde(serialize_with)]`.
+pub(crate) fn serialize_option<S: Serializer, T: Copy + Into<f64> + Serialize>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    value.map(JsonNumber).serialize(serializer)
+}
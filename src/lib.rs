@@ -6,40 +6,100 @@
 //!
 //! In order to use this API, you will need to look over the [list of device parameters](https://github.com/ambient-weather/api-docs/wiki/Device-Data-Specs) that Ambient Weather offers. Not all device parameters may be used, so make sure you are calling one that is associated with your device.
 //!
-//! Currently, this Rust crate is only capable of utilizing the Ambient Weather REST API. Support for their Realtime Socket.IO API will come at a later date.
+//! This Rust crate supports both the Ambient Weather REST API and their Realtime Socket.IO API. The realtime API is available through [`RealtimeClient`], which pushes new data from your devices as soon as it arrives.
+//!
+//! Ambient Weather reports everything in imperial units. Accessors such as [`WeatherData::temperature`] return unit-carrying types like [`Temperature`], which convert to whichever units you need.
+//!
+//! Times are reported as [`Timestamp`]s, which can be shown in a device's own time zone. With the `chrono` feature enabled, they also convert to and from `chrono::DateTime`.
+//!
+//! The functions at the root of this crate are blocking. If you are already inside an async application, use [`AmbientWeatherClient`] instead, which exposes the same calls as `async fn`s and does not start a runtime of its own.
 //!
 //! # Getting Started
 //!
 //! To get started with pulling in the latest weather data from your Ambient Weather device, simply follow the example below:
 //!
-//! ```
+//! ```no_run
 //! use ambient_weather_api::*;
 //!
-//! fn main() {
+//! fn main() -> Result<(), AmbientWeatherError> {
 //!
 //!     let api_credentials = AmbientWeatherAPICredentials {
 //!         api_key: String::from("Your API Key"),
 //!         app_key: String::from("Your Application Key"),
-//!         device_id: 0,
+//!         device: DeviceSelector::Index(0),
 //!         use_new_api_endpoint: false,
 //!     };
 //!     
 //!     // Get the current temperature
-//!     let latest_data = get_latest_device_data(&api_credentials);
-//!     println!("The current temp is: {}F", latest_data.tempf.unwrap());
+//!     let latest_data = get_latest_device_data(&api_credentials)?;
+//!     println!("The current temp is: {}F", latest_data.tempf.unwrap_or_default());
 //!
 //!     // Get the historic temperatures and loop through them going back in time
-//!     let historic_data = get_historic_device_data(&api_credentials);
-//!     for i in 0..historic_data.len() {
-//!         println!("The historic temp was: {}F", historic_data[i].tempf.unwrap());
+//!     let historic_data = get_historic_device_data(&api_credentials)?;
+//!     for data in &historic_data {
+//!         println!("The historic temp was: {}F", data.tempf.unwrap_or_default());
 //!     }
+//!
+//!     Ok(())
 //! }
 //! ```
 
-use serde_json::{self, json, Value};
-use std::{thread, time::Duration};
+use std::{future::Future, time::SystemTime};
 
+mod aggregate;
+mod aqi;
+mod battery;
+mod client;
+mod coverage;
+mod daily_summary;
+mod derived;
+mod device;
+mod engine_io;
+mod error;
+mod field_kind;
+mod historic;
+mod json_number;
+mod rain;
+mod rate_limit;
+mod realtime;
+mod retry;
+mod sensor_channels;
+mod time_zone;
+mod timestamp;
+mod unit_system;
+mod units;
 mod weather_data_struct;
+mod wind;
+
+pub use aggregate::{Aggregate, FieldStatistics, Period};
+pub use aqi::{Aqi, AqiCategory};
+pub use battery::{BatterySensor, BatteryStatus, SensorBattery};
+pub use client::{AmbientWeatherClient, AmbientWeatherClientBuilder};
+pub use coverage::{Coverage, DayCoverage, Gap};
+pub use daily_summary::DailySummary;
+pub use derived::DerivedWeather;
+pub use device::{Coordinates, Device, DeviceInfo, DeviceLocation, DeviceSelector};
+pub use error::AmbientWeatherError;
+pub use historic::{
+    HistoricPages, HistoricQuery, HistoricRecords, RecordError, MAX_HISTORIC_LIMIT,
+};
+pub use rain::{RainEvent, RainInterval};
+pub use rate_limit::RateLimits;
+pub use realtime::{
+    ConnectionState, RealtimeClient, RealtimeClientBuilder, RealtimeData, RealtimeEvent,
+    RealtimeSubscription, ReconnectPolicy, SubscribedEvent,
+};
+pub use retry::RetryPolicy;
+pub use sensor_channels::{Channels, SensorChannels, CHANNEL_COUNT};
+pub use time_zone::TimeZone;
+pub use timestamp::{LocalDateTime, Timestamp, UtcOffset};
+pub use unit_system::{ConvertedWeatherData, UnitSystem, Units};
+pub use units::{
+    Concentration, Irradiance, Length, LengthUnit, Pressure, PressureUnit, Speed, SpeedUnit,
+    Temperature, TemperatureUnit, CO2_MOLAR_MASS,
+};
+pub use weather_data_struct::{DecodeMode, WeatherData};
+pub use wind::{WindDirection, WindVector};
 
 #[derive(Clone)]
 
@@ -49,62 +109,57 @@ pub struct AmbientWeatherAPICredentials {
     pub api_key: String,
     /// The Application key received from Ambient Weather.
     pub app_key: String,
-    /// The device id, which for a user with a single station will be 0.
-    pub device_id: usize,
+    /// The device the blocking functions fetch data for. `DeviceSelector::Index(0)` picks the first station on the account, while selecting by MAC address or name keeps pointing at the same station if devices are added or reordered.
+    pub device: DeviceSelector,
     /// A bool to determine if the new API endpoint should be used. Due to problematic behavior, I recommend leaving this set to false.
     pub use_new_api_endpoint: bool,
 }
 
-/// A private function for crafting the appropriate Ambient Weather API URL.
-fn get_aw_api_url(
-    api_credentials: &AmbientWeatherAPICredentials,
-    device_mac_address: &str,
-) -> String {
-    let url_endpoint = if api_credentials.use_new_api_endpoint {
-        "rt"
-    } else {
-        "api"
-    };
-
-    format!("https://{url_endpoint}.ambientweather.net/v1/devices/{device_mac_address}?applicationKey={}&apiKey={}", api_credentials.app_key, api_credentials.api_key)
+/// A private function that drives a future to completion on a fresh single threaded Tokio runtime, for the blocking functions below.
+fn block_on<T>(
+    future: impl Future<Output = Result<T, AmbientWeatherError>>,
+) -> Result<T, AmbientWeatherError> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(AmbientWeatherError::Runtime)?
+        .block_on(future)
 }
 
-/// A private function that gets the raw device data from the Ambient Weather REST API, and then returns either the latest or the historical data for a device
-#[tokio::main]
-async fn get_raw_device_data(
+/// Lists every device registered to the account from the Ambient Weather API, along with its name, location and latest data.
+///
+/// This is a blocking wrapper around [`AmbientWeatherClient::list_devices`]. It starts its own Tokio runtime, so it must not be called from inside an async context; use [`AmbientWeatherClient`] there instead.
+///
+/// # Errors
+///
+/// Returns an [`AmbientWeatherError`] if the request fails, the keys are rejected, the API rate limits the request, or a device cannot be decoded.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ambient_weather_api::*;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let api_credentials = AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     };
+///
+///     // Print the name and MAC address of every station on the account
+///     for device in get_device_list(&api_credentials)? {
+///         println!("{}: {}", device.mac_address, device.name().unwrap_or("Unnamed"));
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+pub fn get_device_list(
     api_credentials: &AmbientWeatherAPICredentials,
-    device_mac_address: String,
-    retrieve_history: bool,
-) -> Result<Value, reqwest::Error> {
-    let device_id = api_credentials.device_id;
-
-    let response: Value = reqwest::get(get_aw_api_url(api_credentials, &device_mac_address))
-        .await?
-        .json()
-        .await?;
-
-    thread::sleep(Duration::from_millis(1000));
-
-    // If True, this will get and return the historic data for a given device
-    if retrieve_history {
-        let mut device_mac_address =
-            response[device_id].as_object().unwrap()["macAddress"].to_string();
-
-        device_mac_address.pop();
-        device_mac_address.remove(0);
-
-        let historical_response: Value =
-            reqwest::get(get_aw_api_url(api_credentials, &device_mac_address))
-                .await?
-                .json()
-                .await?;
-
-        thread::sleep(Duration::from_millis(1000));
-
-        return Ok(json!(historical_response));
-    }
-
-    Ok(json!(response[device_id]))
+) -> Result<Vec<Device>, AmbientWeatherError> {
+    block_on(AmbientWeatherClient::new(api_credentials.clone()).list_devices())
 }
 
 /// Gets the latest device data from the Ambient Weather API.
@@ -113,77 +168,118 @@ async fn get_raw_device_data(
 ///
 /// When calling the `get_latest_device_data` function, you must pass the api_credentials as a reference (`&api_credentials`), as this allows for it to be called multiple times elsewhere in a program if necessary.
 ///
+/// This is a blocking wrapper around [`AmbientWeatherClient::latest`]. It starts its own Tokio runtime, so it must not be called from inside an async context; use [`AmbientWeatherClient`] there instead.
+///
+/// # Errors
+///
+/// Returns an [`AmbientWeatherError`] if the request fails, the keys are rejected, the API rate limits the request, no device matches `device`, or the device data cannot be decoded.
+///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// use ambient_weather_api::*;
 ///
-/// fn main() {
+/// fn main() -> Result<(), AmbientWeatherError> {
 ///
 ///     let api_credentials = AmbientWeatherAPICredentials {
 ///         api_key: String::from("Your API Key"),
 ///         app_key: String::from("Your Application Key"),
-///         device_id: 0,
+///         device: DeviceSelector::Index(0),
 ///         use_new_api_endpoint: false,
 ///     };
 ///     
 ///     // Get the current temperature
-///     let latest_data = get_latest_device_data(&api_credentials);
-///     println!("The current temp is: {}F", latest_data.tempf.unwrap());
+///     let latest_data = get_latest_device_data(&api_credentials)?;
+///     println!("The current temp is: {}F", latest_data.tempf.unwrap_or_default());
 ///
+///     Ok(())
 /// }
 /// ```
 pub fn get_latest_device_data(
     api_credentials: &AmbientWeatherAPICredentials,
-) -> weather_data_struct::WeatherData {
-    let raw_device_data =
-        get_raw_device_data(api_credentials, "".to_string(), false).unwrap();
+) -> Result<WeatherData, AmbientWeatherError> {
+    let client = AmbientWeatherClient::new(api_credentials.clone());
 
-    serde_json::from_value(json!(raw_device_data["lastData"])).unwrap_or(weather_data_struct::WeatherData::default())
+    block_on(client.latest(&api_credentials.device))
 }
 
 /// Gets the historic device data from the Ambient Weather API.
 ///
-/// As of version 0.3.0 this now functions in an asynchronous manner.
+/// This is a blocking wrapper around [`AmbientWeatherClient::historic`]. It starts its own Tokio runtime, so it must not be called from inside an async context; use [`AmbientWeatherClient`] there instead.
 ///
 /// In order to use this API, you will need to look over the [list of device parameters](https://github.com/ambient-weather/api-docs/wiki/Device-Data-Specs) that Ambient Weather offers. Not all device parameters may be used, so make sure you are calling one that is associated with your device.
 ///
+/// # Errors
+///
+/// Returns an [`AmbientWeatherError`] under the same conditions as [`get_latest_device_data`]. Records that cannot be decoded do not fail the call, and are reported in [`HistoricRecords::errors`] instead.
+///
 /// # Examples
 ///
-/// ```
+/// ```no_run
 /// use ambient_weather_api::*;
 ///
-/// fn main() {
+/// fn main() -> Result<(), AmbientWeatherError> {
 ///
 ///     let api_credentials = AmbientWeatherAPICredentials {
 ///         api_key: String::from("Your API Key"),
 ///         app_key: String::from("Your Application Key"),
-///         device_id: 0,
+///         device: DeviceSelector::Index(0),
 ///         use_new_api_endpoint: false,
 ///     };
 ///     
 ///     // Get the historic temperatures and loop through them going back in time
-///     let historic_data = get_historic_device_data(&api_credentials);
-///        for i in 0..historic_data.len() {
-///            println!("The historic temp was: {}F", historic_data[i].tempf.unwrap());
-///        }
-///     
+///     let historic_data = get_historic_device_data(&api_credentials)?;
+///     for data in &historic_data {
+///         println!("The historic temp was: {}F", data.tempf.unwrap_or_default());
+///     }
+///
+///     Ok(())
 /// }
 /// ```
 pub fn get_historic_device_data(
     api_credentials: &AmbientWeatherAPICredentials,
-) -> Vec<weather_data_struct::WeatherData> {
-    let raw_device_data =
-        get_raw_device_data(api_credentials, "".to_string(), true).unwrap();
+) -> Result<HistoricRecords, AmbientWeatherError> {
+    let client = AmbientWeatherClient::new(api_credentials.clone());
+
+    block_on(client.historic(&api_credentials.device))
+}
 
-    let weather_data_array: Vec<Value> = raw_device_data
-        .as_array()
-        .unwrap()
-        .to_vec();
+/// Gets every historic record for a device from `start` until now from the Ambient Weather API, newest record first.
+///
+/// This walks backward through the device's history one page at a time, so a long time span will make several requests. It is a blocking wrapper around [`AmbientWeatherClient::historic_since`], so it must not be called from inside an async context; use [`AmbientWeatherClient`] there instead.
+///
+/// # Errors
+///
+/// Returns an [`AmbientWeatherError`] under the same conditions as [`get_historic_device_data`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use ambient_weather_api::*;
+/// use std::time::{Duration, SystemTime};
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let api_credentials = AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     };
+///
+///     // Get the last week of temperatures
+///     let start = SystemTime::now() - Duration::from_secs(7 * 24 * 60 * 60);
+///     let historic_data = get_historic_device_data_since(&api_credentials, start)?;
+///     println!("Fetched {} records", historic_data.len());
+///
+///     Ok(())
+/// }
+/// ```
+pub fn get_historic_device_data_since(
+    api_credentials: &AmbientWeatherAPICredentials,
+    start: SystemTime,
+) -> Result<HistoricRecords, AmbientWeatherError> {
+    let client = AmbientWeatherClient::new(api_credentials.clone());
 
-    weather_data_array
-        .into_iter()
-        .map(|data| serde_json::from_value(data)
-        .unwrap())
-        .collect()
+    block_on(client.historic_since(&api_credentials.device, start))
 }
@@ -0,0 +1,295 @@
+use std::time::Duration;
+
+use crate::{
+    aggregate::local_records, daily_summary::counter_increase, Length, LocalDateTime, Timestamp,
+    WeatherData,
+};
+
+/// The rain that fell between two consecutive records, reconstructed from their cumulative rain counters.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RainInterval {
+    /// The time of the earlier record.
+    pub start: Timestamp,
+    /// The time of the later record.
+    pub end: Timestamp,
+    /// The rain that fell between the two records.
+    pub rain: Length,
+}
+
+impl RainInterval {
+    /// Reconstructs the rain that fell between each pair of consecutive records, such as those returned by [`AmbientWeatherClient::historic_since`](crate::AmbientWeatherClient::historic_since), oldest first.
+    ///
+    /// Rain is taken from the rise of `totalrainin` between the two records, or of `dailyrainin` if either record lacks `totalrainin`. A counter lower than in the record before is taken to have been reset in between, and to have counted up from zero since. As `dailyrainin` resets at every local midnight, going by the record's `tz` or UTC, the rain between two records on different days is the later record's `dailyrainin`, which leaves out any rain that fell before the last midnight. Records with neither counter are skipped, so the interval after them spans the gap, as do the intervals across gaps in the records themselves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{RainInterval, WeatherData};
+    ///
+    /// // `dailyrainin` resets at midnight, between the second and third record
+    /// let records: Vec<WeatherData> = [
+    ///     ("2023-10-15T23:50:00.000Z", 0.30),
+    ///     ("2023-10-15T23:55:00.000Z", 0.35),
+    ///     ("2023-10-16T00:00:00.000Z", 0.02),
+    /// ]
+    /// .into_iter()
+    /// .map(|(date, dailyrainin)| serde_json::from_value(serde_json::json!({
+    ///     "dateutc": date,
+    ///     "dailyrainin": dailyrainin,
+    /// })).unwrap())
+    /// .collect();
+    ///
+    /// let intervals = RainInterval::from_records(&records);
+    /// let rain: Vec<f32> = intervals.iter().map(|interval| (interval.rain.inches() * 100.0).round()).collect();
+    ///
+    /// assert_eq!(rain, [5.0, 2.0]);
+    /// ```
+    pub fn from_records(records: &[WeatherData]) -> Vec<RainInterval> {
+        let mut intervals = Vec::new();
+        let mut previous: Option<(Timestamp, LocalDateTime, Option<f32>, Option<f32>)> = None;
+
+        for (time, local_time, data) in local_records(records) {
+            if data.totalrainin.is_none() && data.dailyrainin.is_none() {
+                continue;
+            }
+
+            if let Some((start, start_local_time, total, daily)) =
+                previous.filter(|&(start, ..)| start < time)
+            {
+                let increase = match (total.zip(data.totalrainin), daily.zip(data.dailyrainin)) {
+                    (Some((before, after)), _) => {
+                        Some(counter_increase(f64::from(before), f64::from(after)))
+                    }
+                    // `dailyrainin` resets at every local midnight, so across one it has counted up from zero since.
+                    (None, Some((_, after))) if !same_day(start_local_time, local_time) => {
+                        Some(f64::from(after))
+                    }
+                    (None, Some((before, after))) => {
+                        Some(counter_increase(f64::from(before), f64::from(after)))
+                    }
+                    (None, None) => None,
+                };
+
+                if let Some(increase) = increase {
+                    intervals.push(RainInterval {
+                        start,
+                        end: time,
+                        rain: Length::from_inches(increase as f32),
+                    });
+                }
+            }
+
+            previous = Some((time, local_time, data.totalrainin, data.dailyrainin));
+        }
+
+        intervals
+    }
+
+    /// Returns the time between the two records.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis((self.end.as_millis() - self.start.as_millis()).max(0) as u64)
+    }
+
+    /// Returns the mean rain rate over the interval, as the rain that would fall in an hour. An interval without any length has a rate of zero.
+    pub fn rate(&self) -> Length {
+        let hours = self.duration().as_secs_f64() / 3600.0;
+        if hours == 0.0 {
+            return Length::default();
+        }
+
+        Length::from_metres((f64::from(self.rain.metres()) / hours) as f32)
+    }
+}
+
+/// A period of rain, bounded by dry spells.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RainEvent {
+    /// The start of the first interval rain fell in.
+    pub start: Timestamp,
+    /// The end of the last interval rain fell in.
+    pub end: Timestamp,
+    /// The rain that fell during the event.
+    pub total: Length,
+    /// The highest rain rate of the event, as the rain that would fall in an hour, see [`RainInterval::rate`].
+    pub peak_rate: Length,
+}
+
+impl RainEvent {
+    /// Detects the rain events in a sequence of records, the same way as [`RainEvent::from_intervals`] with the intervals of [`RainInterval::from_records`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{RainEvent, WeatherData};
+    /// use std::time::Duration;
+    ///
+    /// // Two showers, three hours apart
+    /// let records: Vec<WeatherData> = [
+    ///     ("2023-10-15T12:00:00.000Z", 10.00),
+    ///     ("2023-10-15T12:05:00.000Z", 10.05),
+    ///     ("2023-10-15T12:10:00.000Z", 10.07),
+    ///     ("2023-10-15T15:10:00.000Z", 10.07),
+    ///     ("2023-10-15T15:15:00.000Z", 10.11),
+    /// ]
+    /// .into_iter()
+    /// .map(|(date, totalrainin)| serde_json::from_value(serde_json::json!({
+    ///     "dateutc": date,
+    ///     "totalrainin": totalrainin,
+    /// })).unwrap())
+    /// .collect();
+    ///
+    /// let events = RainEvent::from_records(&records, Duration::from_secs(60 * 60));
+    ///
+    /// assert_eq!(events.len(), 2);
+    /// assert_eq!(events[0].end.to_string(), "2023-10-15T12:10:00.000Z");
+    /// assert_eq!((events[0].total.inches() * 100.0).round(), 7.0);
+    /// assert_eq!(events[0].peak_rate.inches().round(), 1.0);
+    /// ```
+    pub fn from_records(records: &[WeatherData], dry_spell: Duration) -> Vec<RainEvent> {
+        RainEvent::from_intervals(&RainInterval::from_records(records), dry_spell)
+    }
+
+    /// Groups rain intervals, oldest first, into events. An event ends once no rain has fallen for at least `dry_spell`, and intervals that span a gap in the records continue the event they fall in, as long as the gap is shorter than `dry_spell` or rain fell over it.
+    pub fn from_intervals(intervals: &[RainInterval], dry_spell: Duration) -> Vec<RainEvent> {
+        let dry_spell = dry_spell.as_millis() as i64;
+        let mut events: Vec<RainEvent> = Vec::new();
+
+        for interval in intervals
+            .iter()
+            .filter(|interval| interval.rain.metres() > 0.0)
+        {
+            match events.last_mut() {
+                Some(event) if interval.start.as_millis() - event.end.as_millis() < dry_spell => {
+                    event.end = interval.end;
+                    event.total =
+                        Length::from_metres(event.total.metres() + interval.rain.metres());
+                    if interval.rate() > event.peak_rate {
+                        event.peak_rate = interval.rate();
+                    }
+                }
+                _ => events.push(RainEvent {
+                    start: interval.start,
+                    end: interval.end,
+                    total: interval.rain,
+                    peak_rate: interval.rate(),
+                }),
+            }
+        }
+
+        events
+    }
+
+    /// Returns the time from the start to the end of the event.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis((self.end.as_millis() - self.start.as_millis()).max(0) as u64)
+    }
+}
+
+/// A private function that checks whether two wall clock times fall on the same calendar day.
+fn same_day(a: LocalDateTime, b: LocalDateTime) -> bool {
+    (a.year, a.month, a.day) == (b.year, b.month, b.day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(readings: &[(&str, &str, f32)]) -> Vec<WeatherData> {
+        readings
+            .iter()
+            .map(|&(date, counter, value)| {
+                serde_json::from_value(serde_json::json!({ "dateutc": date, counter: value }))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    fn hundredths(intervals: &[RainInterval]) -> Vec<f32> {
+        intervals
+            .iter()
+            .map(|interval| (interval.rain.inches() * 100.0).round())
+            .collect()
+    }
+
+    #[test]
+    fn counts_rain_after_a_reset_to_more_than_half() {
+        // `dailyrainin` resets at midnight, and 0.20 in has fallen by the next record
+        let records = records(&[
+            ("2023-10-15T23:30:00.000Z", "dailyrainin", 0.25),
+            ("2023-10-15T23:55:00.000Z", "dailyrainin", 0.30),
+            ("2023-10-16T00:30:00.000Z", "dailyrainin", 0.20),
+        ]);
+
+        assert_eq!(
+            hundredths(&RainInterval::from_records(&records)),
+            [5.0, 20.0]
+        );
+    }
+
+    #[test]
+    fn prefers_the_total_counter() {
+        let mut records = records(&[
+            ("2023-10-15T12:00:00.000Z", "totalrainin", 10.00),
+            ("2023-10-15T12:05:00.000Z", "totalrainin", 10.04),
+            ("2023-10-15T12:10:00.000Z", "dailyrainin", 0.50),
+        ]);
+        records[0].dailyrainin = Some(0.10);
+        records[1].dailyrainin = Some(0.30);
+
+        assert_eq!(
+            hundredths(&RainInterval::from_records(&records)),
+            [4.0, 20.0]
+        );
+    }
+
+    #[test]
+    fn counts_a_rise_after_a_reset_at_midnight() {
+        // The 0.10 in before midnight is gone from the counter, and 0.50 in fell after it
+        let records = records(&[
+            ("2023-10-15T20:00:00.000Z", "dailyrainin", 0.10),
+            ("2023-10-16T08:00:00.000Z", "dailyrainin", 0.50),
+        ]);
+
+        assert_eq!(hundredths(&RainInterval::from_records(&records)), [50.0]);
+    }
+
+    #[test]
+    fn counts_the_last_day_across_a_gap_of_several_days() {
+        let records = records(&[
+            ("2023-10-15T12:00:00.000Z", "dailyrainin", 0.40),
+            ("2023-10-18T12:00:00.000Z", "dailyrainin", 0.30),
+            ("2023-10-18T13:00:00.000Z", "dailyrainin", 0.35),
+        ]);
+
+        assert_eq!(
+            hundredths(&RainInterval::from_records(&records)),
+            [30.0, 5.0]
+        );
+    }
+
+    #[test]
+    fn follows_the_local_midnight_of_the_station() {
+        // 20:00 and 23:00 UTC are both on 15 October in New York, so the counter did not reset in between
+        let mut records = records(&[
+            ("2023-10-15T20:00:00.000Z", "dailyrainin", 0.10),
+            ("2023-10-16T02:00:00.000Z", "dailyrainin", 0.50),
+        ]);
+        for data in &mut records {
+            data.tz = Some(crate::TimeZone::named("America/New_York"));
+        }
+
+        assert_eq!(hundredths(&RainInterval::from_records(&records)), [40.0]);
+    }
+
+    #[test]
+    fn has_no_rate_without_a_duration() {
+        let time = Timestamp::from_millis(1_697_385_600_000);
+        let interval = RainInterval {
+            start: time,
+            end: time,
+            rain: Length::from_inches(0.1),
+        };
+
+        assert_eq!(interval.rate(), Length::default());
+    }
+}
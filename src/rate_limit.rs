@@ -0,0 +1,283 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+/// The request rates an [`AmbientWeatherClient`](crate::AmbientWeatherClient) keeps itself under.
+///
+/// Ambient Weather allows 1 request per second for each API key and 3 requests per second for each Application key. The limits are tracked per key for the whole process, so every clone of a client, every client built from the same keys with the same limits, and every task using them share the same budget. Clients given different limits for the same key keep budgets of their own.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::*;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let api_credentials = AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     };
+///
+///     // Stay well under the limits, as other programs share this Application key
+///     let client = AmbientWeatherClient::builder(api_credentials)
+///         .rate_limits(RateLimits {
+///             per_api_key: 0.5,
+///             per_application_key: 1.0,
+///         })
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimits {
+    /// The number of requests per second allowed for each API key.
+    pub per_api_key: f64,
+    /// The number of requests per second allowed for each Application key.
+    pub per_application_key: f64,
+}
+
+impl Default for RateLimits {
+    fn default() -> Self {
+        RateLimits {
+            per_api_key: 1.0,
+            per_application_key: 3.0,
+        }
+    }
+}
+
+/// A private rate limiter shared by every clone of a client, made up of one token bucket per key.
+#[derive(Clone)]
+pub(crate) struct RateLimiter {
+    api_key_bucket: Arc<TokenBucket>,
+    application_key_bucket: Arc<TokenBucket>,
+}
+
+impl RateLimiter {
+    /// Creates a limiter for a pair of keys, joining the buckets of any other limiter already using them at the same rates.
+    pub(crate) fn new(limits: RateLimits, api_key: &str, app_key: &str) -> Self {
+        RateLimiter {
+            api_key_bucket: shared_bucket(format!("apiKey:{api_key}"), limits.per_api_key),
+            application_key_bucket: shared_bucket(
+                format!("applicationKey:{app_key}"),
+                limits.per_application_key,
+            ),
+        }
+    }
+
+    /// Waits until a request can be sent without going over either key's limit.
+    ///
+    /// The slot is reserved before waiting, so concurrent callers queue up one after another instead of all waking at once.
+    pub(crate) async fn acquire(&self) {
+        let wait = self
+            .api_key_bucket
+            .reserve()
+            .max(self.application_key_bucket.reserve());
+
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+/// A private token bucket holding at most one token, refilled at a fixed rate of tokens per second.
+///
+/// The token count is allowed to go negative, which records requests that have reserved a future slot and are still waiting for it.
+struct TokenBucket {
+    rate: f64,
+    state: Mutex<BucketState>,
+}
+
+struct BucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64) -> Self {
+        TokenBucket {
+            rate,
+            state: Mutex::new(BucketState {
+                tokens: 1.0,
+                last_refill: Instant::now(),
+            }),
+        }
+    }
+
+    /// Returns whether the bucket has refilled to a whole token since it was last used, which makes it no different from a new bucket.
+    fn is_full(&self) -> bool {
+        let state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+        let elapsed = Instant::now()
+            .duration_since(state.last_refill)
+            .as_secs_f64();
+
+        !self.rate.is_finite() || self.rate <= 0.0 || state.tokens + elapsed * self.rate >= 1.0
+    }
+
+    /// Takes a token and returns how long the caller must wait before using it. A rate that is not positive disables the limit.
+    fn reserve(&self) -> Duration {
+        let rate = self.rate;
+        if !rate.is_finite() || rate <= 0.0 {
+            return Duration::ZERO;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|err| err.into_inner());
+
+        let now = Instant::now();
+        let elapsed = now.duration_since(state.last_refill).as_secs_f64();
+        state.tokens = (state.tokens + elapsed * rate).min(1.0);
+        state.last_refill = now;
+        state.tokens -= 1.0;
+
+        if state.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-state.tokens / rate)
+        }
+    }
+}
+
+/// A private alias for what a shared bucket is looked up by: the key it limits and the bits of its rate.
+type BucketKey = (String, u64);
+
+/// A private function that returns the process wide bucket for a key and rate, creating it on first use.
+///
+/// Buckets outlive the clients using them, so the short lived clients of the blocking functions still wait for the requests made before them. A bucket is only forgotten once no limiter uses it and it has refilled since it was last used, when a new bucket would behave the same, so the map does not grow with every key a process has ever seen. Limiters for the same key at different rates get buckets of their own, as one bucket can only refill at one rate.
+fn shared_bucket(key: String, rate: f64) -> Arc<TokenBucket> {
+    static BUCKETS: OnceLock<Mutex<HashMap<BucketKey, Arc<TokenBucket>>>> = OnceLock::new();
+
+    let mut buckets = BUCKETS
+        .get_or_init(Default::default)
+        .lock()
+        .unwrap_or_else(|err| err.into_inner());
+
+    buckets.retain(|_, bucket| Arc::strong_count(bucket) > 1 || !bucket.is_full());
+
+    buckets
+        .entry((key, rate.to_bits()))
+        .or_insert_with(|| Arc::new(TokenBucket::new(rate)))
+        .clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn refills_at_the_bucket_rate() {
+        let bucket = TokenBucket::new(2.0);
+
+        // Reservations queue up behind each other, half a second apart
+        let waits: Vec<Duration> = (0..3).map(|_| bucket.reserve()).collect();
+        assert_eq!(
+            waits,
+            [
+                Duration::ZERO,
+                Duration::from_millis(500),
+                Duration::from_secs(1)
+            ]
+        );
+
+        // Once the queue has drained, the bucket refills to a single token and no more
+        tokio::time::advance(Duration::from_secs(10)).await;
+        assert_eq!(bucket.reserve(), Duration::ZERO);
+        assert_eq!(bucket.reserve(), Duration::from_millis(500));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn ignores_rates_that_are_not_positive() {
+        for rate in [0.0, -1.0, f64::NAN, f64::INFINITY] {
+            let bucket = TokenBucket::new(rate);
+            assert!((0..5).all(|_| bucket.reserve().is_zero()));
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn spaces_out_requests() {
+        let limits = RateLimits {
+            per_api_key: 1.0,
+            per_application_key: 4.0,
+        };
+        let limiter = RateLimiter::new(limits, "spacing-api-key", "spacing-app-key");
+
+        let started = Instant::now();
+        for _ in 0..3 {
+            limiter.acquire().await;
+        }
+
+        assert_eq!(started.elapsed(), Duration::from_secs(2));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn shares_buckets_only_at_the_same_rates() {
+        let slow = RateLimits {
+            per_api_key: 1.0,
+            per_application_key: 3.0,
+        };
+        let fast = RateLimits {
+            per_api_key: 5.0,
+            ..slow
+        };
+
+        let first = RateLimiter::new(slow, "shared-api-key", "shared-app-key");
+        let second = RateLimiter::new(slow, "shared-api-key", "shared-app-key");
+        let third = RateLimiter::new(fast, "shared-api-key", "shared-app-key");
+
+        assert!(Arc::ptr_eq(&first.api_key_bucket, &second.api_key_bucket));
+        assert!(!Arc::ptr_eq(&first.api_key_bucket, &third.api_key_bucket));
+        assert!(Arc::ptr_eq(
+            &first.application_key_bucket,
+            &third.application_key_bucket
+        ));
+        assert_eq!(third.api_key_bucket.rate, 5.0);
+    }
+
+    #[test]
+    fn spaces_consecutive_blocking_calls() {
+        // Each blocking function builds its own client and runtime, and drops both before returning
+        let blocking_call = || {
+            crate::block_on(async {
+                RateLimiter::new(
+                    RateLimits::default(),
+                    "blocking-api-key",
+                    "blocking-app-key",
+                )
+                .acquire()
+                .await;
+                Ok(std::time::Instant::now())
+            })
+            .unwrap()
+        };
+
+        let first = blocking_call();
+        let second = blocking_call();
+
+        assert!(second.duration_since(first) >= Duration::from_millis(990));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn forgets_buckets_once_unused_and_refilled() {
+        let limiter = RateLimiter::new(RateLimits::default(), "idle-api-key", "idle-app-key");
+        limiter.acquire().await;
+        let bucket = Arc::downgrade(&limiter.api_key_bucket);
+        drop(limiter);
+
+        // A bucket that has not refilled yet is kept for the next limiter
+        let limiter = RateLimiter::new(RateLimits::default(), "idle-api-key", "idle-app-key");
+        assert!(Arc::ptr_eq(
+            &bucket.upgrade().unwrap(),
+            &limiter.api_key_bucket
+        ));
+        drop(limiter);
+
+        tokio::time::advance(Duration::from_secs(1)).await;
+        RateLimiter::new(RateLimits::default(), "other-api-key", "other-app-key");
+
+        assert!(bucket.upgrade().is_none());
+    }
+}
@@ -0,0 +1,690 @@
+use futures_core::Stream;
+use reqwest::Proxy;
+use serde_json::{json, Value};
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::{
+    sync::mpsc,
+    task::JoinHandle,
+    time::{self, Instant, MissedTickBehavior},
+};
+
+use crate::{
+    device,
+    engine_io::{Packet, PollingSession},
+    retry, weather_data_struct, AmbientWeatherError, DecodeMode, Device, WeatherData,
+};
+
+/// A client for the Ambient Weather Realtime Socket.IO API, which pushes new data from your devices as soon as it arrives instead of having to poll the REST API for it.
+///
+/// The client speaks Socket.IO over the Engine.IO long-polling transport, reusing the same HTTP stack as [`AmbientWeatherClient`](crate::AmbientWeatherClient). Use [`RealtimeClient::builder`] to point it at a local Socket.IO stand-in server for testing.
+///
+/// Subscriptions keep themselves alive: they exchange Engine.IO heartbeats with the server, and when the connection drops they reconnect with exponential backoff and subscribe all of their API keys again. Every step of that is reported as a [`RealtimeEvent::Connection`] event.
+///
+/// # Examples
+///
+/// ```no_run
+/// use ambient_weather_api::*;
+///
+/// #[tokio::main]
+/// async fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let client = RealtimeClient::new("Your Application Key");
+///     let mut subscription = client.subscribe(["Your API Key"]).await?;
+///
+///     // Print the temperature every time a device reports in
+///     while let Some(event) = subscription.next_event().await {
+///         if let RealtimeEvent::Data(update) = event? {
+///             println!("{} is now {}F", update.mac_address, update.data.tempf.unwrap_or_default());
+///         }
+///     }
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Clone)]
+pub struct RealtimeClient {
+    http: reqwest::Client,
+    base_url: String,
+    app_key: String,
+    reconnect_policy: ReconnectPolicy,
+    silence_timeout: Option<Duration>,
+    decode_mode: DecodeMode,
+}
+
+impl RealtimeClient {
+    /// Creates a new realtime client from an Application key, using the default settings.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the underlying HTTP client cannot be initialized, in the same way [`reqwest::Client::new`] does. Use [`RealtimeClient::builder`] to handle that error instead.
+    pub fn new(app_key: impl Into<String>) -> Self {
+        Self::builder(app_key)
+            .build()
+            .expect("failed to initialize the HTTP client")
+    }
+
+    /// Creates a builder for configuring a realtime client from an Application key.
+    pub fn builder(app_key: impl Into<String>) -> RealtimeClientBuilder {
+        RealtimeClientBuilder::new(app_key.into())
+    }
+
+    /// Returns the base URL this client connects to.
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Connects to the realtime API and subscribes to the devices of every given API key.
+    ///
+    /// The returned [`RealtimeSubscription`] yields a [`RealtimeEvent::Subscribed`] once the server confirms the subscription, followed by a [`RealtimeEvent::Data`] for every update any of the devices reports.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`AmbientWeatherError`] if the connection cannot be opened or the subscription cannot be sent. Errors after that point are yielded by the subscription itself.
+    pub async fn subscribe<I, K>(
+        &self,
+        api_keys: I,
+    ) -> Result<RealtimeSubscription, AmbientWeatherError>
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        let api_keys: Vec<String> = api_keys.into_iter().map(Into::into).collect();
+
+        let (session, packets) = self.connect(&api_keys).await?;
+
+        let (sender, receiver) = mpsc::channel(EVENT_BUFFER);
+        let task = tokio::spawn(run_subscription(
+            self.clone(),
+            api_keys,
+            session,
+            packets,
+            sender,
+        ));
+
+        Ok(RealtimeSubscription { receiver, task })
+    }
+
+    /// A private function that opens a new session and subscribes it to every given API key.
+    async fn connect(
+        &self,
+        api_keys: &[String],
+    ) -> Result<(PollingSession, Vec<Packet>), AmbientWeatherError> {
+        let (session, packets) = PollingSession::connect(
+            self.http.clone(),
+            format!("{}/socket.io/", self.base_url),
+            vec![
+                ("api".to_string(), "1".to_string()),
+                ("applicationKey".to_string(), self.app_key.clone()),
+            ],
+        )
+        .await?;
+
+        session
+            .send(&[Packet::Message(format!(
+                "2{}",
+                json!(["subscribe", { "apiKeys": api_keys }])
+            ))])
+            .await?;
+
+        Ok((session, packets))
+    }
+}
+
+/// A builder for configuring a [`RealtimeClient`].
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::*;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let client = RealtimeClient::builder("Your Application Key")
+///         .base_url("http://localhost:3000")
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+pub struct RealtimeClientBuilder {
+    app_key: String,
+    base_url: String,
+    connect_timeout: Option<Duration>,
+    user_agent: String,
+    proxy: Option<Proxy>,
+    reconnect_policy: ReconnectPolicy,
+    silence_timeout: Option<Duration>,
+    decode_mode: DecodeMode,
+}
+
+impl RealtimeClientBuilder {
+    /// A private function for creating a builder with the default settings.
+    fn new(app_key: String) -> Self {
+        RealtimeClientBuilder {
+            app_key,
+            base_url: DEFAULT_REALTIME_BASE_URL.to_string(),
+            connect_timeout: Some(DEFAULT_CONNECT_TIMEOUT),
+            user_agent: crate::client::DEFAULT_USER_AGENT.to_string(),
+            proxy: None,
+            reconnect_policy: ReconnectPolicy::default(),
+            silence_timeout: None,
+            decode_mode: DecodeMode::default(),
+        }
+    }
+
+    /// Sets the base URL to connect to, such as `http://localhost:3000` for a local stand-in server. Defaults to `https://rt2.ambientweather.net`.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Sets the timeout for establishing a connection. Defaults to 30 seconds.
+    ///
+    /// There is no total timeout for each request, as the server deliberately holds polls open until it has something to send.
+    pub fn connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with each request. Defaults to `ambient-weather-api/<version>`.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Sends all requests through the given proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Sets how subscriptions reconnect after their connection drops. Defaults to [`ReconnectPolicy::default`], which keeps trying forever.
+    pub fn reconnect_policy(mut self, reconnect_policy: ReconnectPolicy) -> Self {
+        self.reconnect_policy = reconnect_policy;
+        self
+    }
+
+    /// Reports a [`ConnectionState::Silent`] event whenever no data has arrived for `silence_timeout`, even though the connection itself is still alive. Off by default.
+    ///
+    /// Devices report roughly once a minute, so a few minutes is a sensible value for catching a station that has stopped reporting.
+    pub fn silence_timeout(mut self, silence_timeout: Duration) -> Self {
+        self.silence_timeout = Some(silence_timeout);
+        self
+    }
+
+    /// Sets how strictly records are decoded. Defaults to [`DecodeMode::Lenient`].
+    pub fn decode_mode(mut self, decode_mode: DecodeMode) -> Self {
+        self.decode_mode = decode_mode;
+        self
+    }
+
+    /// Builds the client.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AmbientWeatherError::Http`] if the underlying HTTP client cannot be initialized.
+    pub fn build(self) -> Result<RealtimeClient, AmbientWeatherError> {
+        let mut http = reqwest::Client::builder().user_agent(self.user_agent);
+
+        if let Some(connect_timeout) = self.connect_timeout {
+            http = http.connect_timeout(connect_timeout);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+
+        Ok(RealtimeClient {
+            http: http.build()?,
+            base_url: self.base_url.trim_end_matches('/').to_string(),
+            app_key: self.app_key,
+            reconnect_policy: self.reconnect_policy,
+            silence_timeout: self.silence_timeout,
+            decode_mode: self.decode_mode,
+        })
+    }
+}
+
+/// How a subscription reconnects after its connection to the realtime API drops.
+///
+/// The delay before each attempt grows exponentially from `initial_delay` by `multiplier`, is capped at `max_delay`, and is then shortened by a random amount of up to `jitter` of itself. The attempt count starts over once a reconnect succeeds.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::*;
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     // Give up after ten failed attempts in a row
+///     let client = RealtimeClient::builder("Your Application Key")
+///         .reconnect_policy(ReconnectPolicy {
+///             max_attempts: Some(10),
+///             ..ReconnectPolicy::default()
+///         })
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReconnectPolicy {
+    /// How many reconnect attempts in a row may fail before the subscription gives up. `None` keeps trying forever.
+    pub max_attempts: Option<u32>,
+    /// The delay before the first attempt.
+    pub initial_delay: Duration,
+    /// The longest delay between two attempts, before jitter is applied.
+    pub max_delay: Duration,
+    /// The factor the delay grows by after each failed attempt.
+    pub multiplier: f64,
+    /// The largest fraction of each delay, between 0.0 and 1.0, that may be randomly taken off it.
+    pub jitter: f64,
+}
+
+impl ReconnectPolicy {
+    /// A policy that never reconnects, ending the subscription as soon as the connection drops.
+    pub fn never() -> Self {
+        ReconnectPolicy {
+            max_attempts: Some(0),
+            ..ReconnectPolicy::default()
+        }
+    }
+
+    /// A private function that returns how long to wait before the given attempt, where the first attempt is 1.
+    fn delay(&self, attempt: u32) -> Duration {
+        retry::exponential_backoff(
+            self.initial_delay,
+            self.max_delay,
+            self.multiplier,
+            self.jitter,
+            attempt,
+        )
+    }
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: None,
+            initial_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(60),
+            multiplier: 2.0,
+            jitter: 0.25,
+        }
+    }
+}
+
+/// An event received from the Ambient Weather Realtime API.
+#[derive(Debug, Clone)]
+pub enum RealtimeEvent {
+    /// The server confirmed a subscription, listing the devices it covers. This is sent again after every reconnect.
+    Subscribed(SubscribedEvent),
+    /// A device reported new data.
+    Data(Box<RealtimeData>),
+    /// The state of the connection changed.
+    Connection(ConnectionState),
+}
+
+/// A change in the state of a realtime connection, reported through [`RealtimeEvent::Connection`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    /// The connection dropped, for the given reason. A reconnect follows unless the [`ReconnectPolicy`] forbids it.
+    Disconnected {
+        /// Why the connection was considered lost.
+        reason: String,
+    },
+    /// A reconnect attempt will be made after `delay`.
+    Reconnecting {
+        /// The number of this attempt, starting at 1.
+        attempt: u32,
+        /// How long the subscription waits before making the attempt.
+        delay: Duration,
+    },
+    /// The connection was re-established and every API key has been subscribed again.
+    Reconnected,
+    /// The connection is alive, but no data has arrived for the configured [`RealtimeClientBuilder::silence_timeout`].
+    Silent {
+        /// How long it has been since the last data arrived.
+        since: Duration,
+    },
+}
+
+/// The payload of a `subscribed` event.
+#[derive(Debug, Clone, Default)]
+pub struct SubscribedEvent {
+    /// Every device the subscription now covers, across all of the subscribed API keys.
+    pub devices: Vec<Device>,
+}
+
+/// The payload of a `data` event.
+#[derive(Debug, Clone, Default)]
+pub struct RealtimeData {
+    /// The MAC address of the device that reported the data.
+    pub mac_address: String,
+    /// The reported weather data.
+    pub data: WeatherData,
+}
+
+/// A live subscription to the Ambient Weather Realtime API, created by [`RealtimeClient::subscribe`].
+///
+/// The subscription is a [`Stream`] of events, and also offers [`RealtimeSubscription::next_event`] for use without a stream combinator library. The connection is closed when the subscription is dropped.
+///
+/// An event that cannot be decoded is returned as an error without ending the subscription. The subscription only ends once reconnecting has failed as many times as the [`ReconnectPolicy`] allows.
+pub struct RealtimeSubscription {
+    receiver: mpsc::Receiver<Result<RealtimeEvent, AmbientWeatherError>>,
+    task: JoinHandle<()>,
+}
+
+impl RealtimeSubscription {
+    /// Waits for the next event. Returns `None` once the subscription has given up reconnecting, right after an [`AmbientWeatherError::RetriesExhausted`] error, or after the error the connection dropped with if the [`ReconnectPolicy`] never reconnects.
+    pub async fn next_event(&mut self) -> Option<Result<RealtimeEvent, AmbientWeatherError>> {
+        self.receiver.recv().await
+    }
+}
+
+impl Stream for RealtimeSubscription {
+    type Item = Result<RealtimeEvent, AmbientWeatherError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+impl Drop for RealtimeSubscription {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A private alias for the sending half of a subscription's event channel.
+type EventSender = mpsc::Sender<Result<RealtimeEvent, AmbientWeatherError>>;
+
+/// A private function that forwards the events of a subscription for as long as it lives, reconnecting and subscribing again whenever the connection drops.
+async fn run_subscription(
+    client: RealtimeClient,
+    api_keys: Vec<String>,
+    mut session: PollingSession,
+    mut packets: Vec<Packet>,
+    sender: EventSender,
+) {
+    loop {
+        let reason = match drive_session(&client, &session, packets, &sender).await {
+            Some(reason) => reason,
+            None => return,
+        };
+
+        if !emit(
+            &sender,
+            ConnectionState::Disconnected {
+                reason: reason.clone(),
+            },
+        )
+        .await
+        {
+            return;
+        }
+
+        let mut last_error = AmbientWeatherError::Realtime(reason);
+        let mut attempt = 1;
+
+        (session, packets) = loop {
+            if let Some(max_attempts) = client.reconnect_policy.max_attempts {
+                if attempt > max_attempts {
+                    // Without a single attempt there is nothing to have exhausted, so the disconnect itself is the error.
+                    let error = match max_attempts {
+                        0 => last_error,
+                        _ => AmbientWeatherError::RetriesExhausted {
+                            attempts: max_attempts,
+                            last_error: Box::new(last_error),
+                        },
+                    };
+                    let _ = sender.send(Err(error)).await;
+                    return;
+                }
+            }
+
+            let delay = client.reconnect_policy.delay(attempt);
+            if !emit(&sender, ConnectionState::Reconnecting { attempt, delay }).await {
+                return;
+            }
+            time::sleep(delay).await;
+
+            match client.connect(&api_keys).await {
+                Ok(connection) => break connection,
+                Err(err) => last_error = err,
+            }
+            attempt += 1;
+        };
+
+        if !emit(&sender, ConnectionState::Reconnected).await {
+            return;
+        }
+    }
+}
+
+/// A private function that keeps polling a single session, forwarding its events and exchanging heartbeats, until the session is lost.
+///
+/// Returns the reason the session was lost, or `None` if the subscription was dropped.
+async fn drive_session(
+    client: &RealtimeClient,
+    session: &PollingSession,
+    mut packets: Vec<Packet>,
+    sender: &EventSender,
+) -> Option<String> {
+    let ping_interval = Duration::from_millis(session.open().ping_interval);
+    let heartbeat_timeout = ping_interval + Duration::from_millis(session.open().ping_timeout);
+
+    // Engine.IO 3 expects the client to send a ping every interval, which the server answers with a pong.
+    let mut ping = time::interval_at(Instant::now() + ping_interval, ping_interval);
+    ping.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let mut last_heard = Instant::now();
+    let mut last_data = Instant::now();
+    let mut silence_reported = false;
+
+    let poll = session.poll();
+    tokio::pin!(poll);
+
+    loop {
+        for packet in packets.drain(..) {
+            match packet {
+                Packet::Message(message) => match decode_event(&message, client.decode_mode) {
+                    Incoming::Event(event) => {
+                        if let RealtimeEvent::Data(_) = event {
+                            last_data = Instant::now();
+                            silence_reported = false;
+                        }
+                        if sender.send(Ok(event)).await.is_err() {
+                            return None;
+                        }
+                    }
+                    Incoming::Invalid(err) => {
+                        if sender.send(Err(err)).await.is_err() {
+                            return None;
+                        }
+                    }
+                    Incoming::Disconnected(reason) => return Some(reason),
+                    Incoming::Ignored => {}
+                },
+                // Engine.IO 4 servers send the pings themselves and expect a pong back.
+                Packet::Ping(data) => {
+                    if let Err(reason) =
+                        send_within(session, &[Packet::Pong(data)], heartbeat_timeout).await
+                    {
+                        return Some(reason);
+                    }
+                }
+                Packet::Close => return Some("the server closed the session".to_string()),
+                _ => {}
+            }
+        }
+
+        let silence_deadline = client.silence_timeout.map(|timeout| last_data + timeout);
+
+        tokio::select! {
+            result = &mut poll => match result {
+                Ok(received) => {
+                    packets = received;
+                    last_heard = Instant::now();
+                    poll.set(session.poll());
+                }
+                Err(err) => return Some(err.to_string()),
+            },
+            _ = ping.tick() => {
+                if let Err(reason) =
+                    send_within(session, &[Packet::Ping(String::new())], heartbeat_timeout).await
+                {
+                    return Some(reason);
+                }
+            }
+            _ = time::sleep_until(last_heard + heartbeat_timeout) => {
+                return Some(format!(
+                    "no heartbeat from the server for {}ms",
+                    heartbeat_timeout.as_millis()
+                ));
+            }
+            _ = time::sleep_until(silence_deadline.unwrap_or(last_heard + heartbeat_timeout)),
+                if silence_deadline.is_some() && !silence_reported =>
+            {
+                silence_reported = true;
+                let since = last_data.elapsed();
+                if !emit(sender, ConnectionState::Silent { since }).await {
+                    return None;
+                }
+            }
+        }
+    }
+}
+
+/// A private function that sends packets to the server, giving up after `timeout`. A send that takes longer than the heartbeat timeout means the connection is as good as dead, and would otherwise hold up the heartbeat checks waiting on it. Returns the reason the send failed.
+async fn send_within(
+    session: &PollingSession,
+    packets: &[Packet],
+    timeout: Duration,
+) -> Result<(), String> {
+    match time::timeout(timeout, session.send(packets)).await {
+        Ok(result) => result.map_err(|err| err.to_string()),
+        Err(_) => Err(format!(
+            "sending to the server took longer than {}ms",
+            timeout.as_millis()
+        )),
+    }
+}
+
+/// A private function that reports a connection state change. Returns `false` if the subscription was dropped.
+async fn emit(sender: &EventSender, state: ConnectionState) -> bool {
+    sender
+        .send(Ok(RealtimeEvent::Connection(state)))
+        .await
+        .is_ok()
+}
+
+/// A private representation of what a Socket.IO packet means for a subscription.
+enum Incoming {
+    /// An event to forward.
+    Event(RealtimeEvent),
+    /// An event that could not be decoded.
+    Invalid(AmbientWeatherError),
+    /// The server ended the socket, for the given reason.
+    Disconnected(String),
+    /// A packet this crate has no use for.
+    Ignored,
+}
+
+/// A private function that decodes a Socket.IO packet carried in an Engine.IO message.
+fn decode_event(message: &str, mode: DecodeMode) -> Incoming {
+    let mut chars = message.chars();
+    let packet_type = chars.next();
+    let mut data = chars.as_str();
+
+    // Skip a namespace prefix such as `/devices,`, since everything this crate uses lives in the default namespace.
+    if data.starts_with('/') {
+        data = data.split_once(',').map_or("", |(_, rest)| rest);
+    }
+
+    match packet_type {
+        Some('1') => Incoming::Disconnected("the server disconnected the socket".to_string()),
+        Some('4') => Incoming::Disconnected(format!("the server reported an error: {data}")),
+        Some('2') => {
+            // Skip an acknowledgement id, which precedes the event's JSON array.
+            let data = data.trim_start_matches(|character: char| character.is_ascii_digit());
+
+            let event: Vec<Value> = match serde_json::from_str(data) {
+                Ok(event) => event,
+                Err(err) => {
+                    return Incoming::Invalid(AmbientWeatherError::decode(
+                        Some("event".to_string()),
+                        err,
+                    ))
+                }
+            };
+
+            let decoded = match (event.first().and_then(Value::as_str), event.get(1)) {
+                (Some("subscribed"), Some(payload)) => decode_subscribed(payload, mode),
+                (Some("data"), Some(payload)) => decode_data(payload, mode),
+                _ => return Incoming::Ignored,
+            };
+
+            match decoded {
+                Ok(event) => Incoming::Event(event),
+                Err(err) => Incoming::Invalid(err),
+            }
+        }
+        _ => Incoming::Ignored,
+    }
+}
+
+/// A private function that decodes the payload of a `subscribed` event.
+fn decode_subscribed(
+    payload: &Value,
+    mode: DecodeMode,
+) -> Result<RealtimeEvent, AmbientWeatherError> {
+    let devices = match payload.get("devices") {
+        Some(Value::Array(devices)) => devices
+            .iter()
+            .map(|device| device::decode_device(device, mode))
+            .collect::<Result<_, _>>()?,
+        _ => Vec::new(),
+    };
+
+    Ok(RealtimeEvent::Subscribed(SubscribedEvent { devices }))
+}
+
+/// A private function that decodes the payload of a `data` event, which is a weather data record with the device's MAC address mixed in.
+fn decode_data(payload: &Value, mode: DecodeMode) -> Result<RealtimeEvent, AmbientWeatherError> {
+    let mac_address = payload
+        .get("macAddress")
+        .and_then(Value::as_str)
+        .ok_or_else(|| {
+            AmbientWeatherError::decode(
+                Some("macAddress".to_string()),
+                serde::de::Error::missing_field("macAddress"),
+            )
+        })?
+        .to_string();
+
+    // The device details mixed into the record are not weather data, so keep them out of `WeatherData::extra`.
+    let mut record = payload.clone();
+    if let Some(record) = record.as_object_mut() {
+        record.remove("macAddress");
+        record.remove("device");
+    }
+
+    Ok(RealtimeEvent::Data(Box::new(RealtimeData {
+        mac_address,
+        data: weather_data_struct::decode_weather_data(&record, mode)?,
+    })))
+}
+
+/// The default Ambient Weather Realtime API base URL.
+const DEFAULT_REALTIME_BASE_URL: &str = "https://rt2.ambientweather.net";
+
+/// The default timeout for establishing a connection.
+const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How many events may be waiting to be read before the connection stops polling for more.
+const EVENT_BUFFER: usize = 64;
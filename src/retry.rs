@@ -0,0 +1,203 @@
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::Duration,
+};
+
+use crate::AmbientWeatherError;
+
+/// How an [`AmbientWeatherClient`](crate::AmbientWeatherClient) retries requests that fail with `429 Too Many Requests`, a `5xx` server error, a timeout or a connection error.
+///
+/// The wait before each retry grows exponentially from `initial_backoff` by `multiplier`, is capped at `max_backoff`, and is then shortened by a random amount of up to `jitter` of itself so that many clients do not retry in lockstep. When the API sends a `Retry-After` header, as a number of seconds or an HTTP date, and `honor_retry_after` is set, that wait is used instead, capped at `max_backoff` as well.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::*;
+/// use std::time::Duration;
+///
+/// fn main() -> Result<(), AmbientWeatherError> {
+///
+///     let api_credentials = AmbientWeatherAPICredentials {
+///         api_key: String::from("Your API Key"),
+///         app_key: String::from("Your Application Key"),
+///         device: DeviceSelector::Index(0),
+///         use_new_api_endpoint: false,
+///     };
+///
+///     let client = AmbientWeatherClient::builder(api_credentials)
+///         .retry_policy(RetryPolicy {
+///             max_attempts: 5,
+///             initial_backoff: Duration::from_millis(500),
+///             ..RetryPolicy::default()
+///         })
+///         .build()?;
+///
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+    /// The total number of attempts made for a request, including the first one. A value of 0 or 1 disables retries.
+    pub max_attempts: u32,
+    /// The wait before the first retry.
+    pub initial_backoff: Duration,
+    /// The longest wait between two attempts, before jitter is applied.
+    pub max_backoff: Duration,
+    /// The factor the wait grows by after each retry.
+    pub multiplier: f64,
+    /// The largest fraction of each wait, between 0.0 and 1.0, that may be randomly taken off it.
+    pub jitter: f64,
+    /// Whether to wait as long as a `Retry-After` header asks, up to `max_backoff`, instead of the computed backoff.
+    pub honor_retry_after: bool,
+}
+
+impl RetryPolicy {
+    /// A policy that never retries.
+    pub fn none() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// A private function that returns how long to wait before the given retry, where the first retry is 1.
+    pub(crate) fn backoff(&self, retry: u32, error: &AmbientWeatherError) -> Duration {
+        if let AmbientWeatherError::RateLimited {
+            retry_after: Some(retry_after),
+        } = error
+        {
+            if self.honor_retry_after {
+                return (*retry_after).min(self.max_backoff);
+            }
+        }
+
+        exponential_backoff(
+            self.initial_backoff,
+            self.max_backoff,
+            self.multiplier,
+            self.jitter,
+            retry,
+        )
+    }
+
+    /// A private function that decides whether an error is worth retrying.
+    pub(crate) fn is_retryable(error: &AmbientWeatherError) -> bool {
+        match error {
+            AmbientWeatherError::RateLimited { .. } => true,
+            AmbientWeatherError::Http(err) => match err.status() {
+                Some(status) => status.is_server_error(),
+                None => err.is_timeout() || err.is_connect() || err.is_request(),
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: 0.25,
+            honor_retry_after: true,
+        }
+    }
+}
+
+/// A private function that computes the wait before the given retry, where the first retry is 1: `initial` grown by `multiplier` for each earlier retry, capped at `max`, then shortened by a random amount of up to `jitter` of itself.
+pub(crate) fn exponential_backoff(
+    initial: Duration,
+    max: Duration,
+    multiplier: f64,
+    jitter: f64,
+    retry: u32,
+) -> Duration {
+    let exponent = retry.saturating_sub(1).min(i32::MAX as u32) as i32;
+    let backoff = initial.as_secs_f64() * multiplier.max(1.0).powi(exponent);
+    let backoff = backoff.min(max.as_secs_f64());
+
+    let jitter = jitter.clamp(0.0, 1.0) * random_fraction();
+
+    Duration::try_from_secs_f64(backoff * (1.0 - jitter)).unwrap_or(max)
+}
+
+/// A private function returning a random number between 0.0 and 1.0, seeded from the standard library's randomly keyed hasher.
+fn random_fraction() -> f64 {
+    let random = RandomState::new().build_hasher().finish();
+
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(10),
+            multiplier: 2.0,
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn grows_the_backoff_up_to_the_cap() {
+        let policy = policy();
+        let error = AmbientWeatherError::RateLimited { retry_after: None };
+        let waits: Vec<u64> = (1..=6)
+            .map(|retry| policy.backoff(retry, &error).as_secs())
+            .collect();
+
+        assert_eq!(waits, [1, 2, 4, 8, 10, 10]);
+        assert_eq!(policy.backoff(u32::MAX, &error), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn takes_jitter_off_the_backoff() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..policy()
+        };
+        let error = AmbientWeatherError::RateLimited { retry_after: None };
+
+        for _ in 0..100 {
+            let wait = policy.backoff(3, &error);
+            assert!(Duration::from_secs(2) <= wait && wait <= Duration::from_secs(4));
+        }
+    }
+
+    #[test]
+    fn honors_retry_after_up_to_the_cap() {
+        let policy = policy();
+        let asked = |seconds| AmbientWeatherError::RateLimited {
+            retry_after: Some(Duration::from_secs(seconds)),
+        };
+
+        assert_eq!(policy.backoff(1, &asked(5)), Duration::from_secs(5));
+        assert_eq!(policy.backoff(1, &asked(3600)), Duration::from_secs(10));
+
+        let ignoring = RetryPolicy {
+            honor_retry_after: false,
+            ..policy
+        };
+        assert_eq!(ignoring.backoff(1, &asked(5)), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn retries_only_transient_errors() {
+        assert!(RetryPolicy::is_retryable(
+            &AmbientWeatherError::RateLimited { retry_after: None }
+        ));
+        assert!(!RetryPolicy::is_retryable(
+            &AmbientWeatherError::Unauthorized
+        ));
+        assert!(!RetryPolicy::is_retryable(&AmbientWeatherError::Realtime(
+            String::from("closed")
+        )));
+    }
+}
@@ -0,0 +1,279 @@
+use serde::{
+    de::{self, DeserializeOwned, MapAccess, Visitor},
+    ser::SerializeMap,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+
+use crate::json_number::JsonNumber;
+
+/// The number of channels Ambient Weather supports for each kind of numbered sensor.
+pub const CHANNEL_COUNT: usize = 10;
+
+/// The readings of one kind of numbered sensor, such as `temp1f` through `temp10f`.
+///
+/// Channels are numbered from 1, matching the numbers in Ambient Weather's field names, so `get(1)` is the reading of `temp1f`.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::WeatherData;
+///
+/// let data: WeatherData = serde_json::from_str(r#"{ "temp1f": 68.2, "temp3f": 41.0 }"#).unwrap();
+///
+/// assert_eq!(data.channels.temperature.get(1), Some(68.2));
+/// assert_eq!(data.channels.temperature.get(2), None);
+///
+/// let reporting: Vec<usize> = data.channels.temperature.iter().map(|(channel, _)| channel).collect();
+/// assert_eq!(reporting, [1, 3]);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Channels<T>([Option<T>; CHANNEL_COUNT]);
+
+impl<T: Copy> Channels<T> {
+    /// Returns the reading of a channel, numbered from 1. Returns `None` if the channel did not report or is out of range.
+    pub fn get(&self, channel: usize) -> Option<T> {
+        channel
+            .checked_sub(1)
+            .and_then(|index| self.0.get(index).copied().flatten())
+    }
+
+    /// Iterates over the channels that reported, as `(channel, reading)` pairs numbered from 1.
+    pub fn iter(&self) -> impl Iterator<Item = (usize, T)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .filter_map(|(index, value)| value.map(|value| (index + 1, value)))
+    }
+}
+
+impl<T> Channels<T> {
+    /// Sets the reading of a channel, numbered from 1. Channels out of range are ignored.
+    pub fn set(&mut self, channel: usize, value: Option<T>) {
+        if let Some(slot) = channel
+            .checked_sub(1)
+            .and_then(|index| self.0.get_mut(index))
+        {
+            *slot = value;
+        }
+    }
+
+    /// Returns `true` if no channel reported.
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(Option::is_none)
+    }
+}
+
+impl<T> Default for Channels<T> {
+    fn default() -> Self {
+        Channels(std::array::from_fn(|_| None))
+    }
+}
+
+/// The numbered sensor channels of a [`WeatherData`](crate::WeatherData) record, such as the extra temperature and humidity sensors, soil sensors, leak detectors and relays.
+///
+/// When (de)serialized, each collection maps to Ambient Weather's numbered field names, which are given next to each field.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SensorChannels {
+    /// `temp1f` to `temp10f`: the temperature of each extra sensor, in °F.
+    pub temperature: Channels<f32>,
+    /// `humidity1` to `humidity10`: the relative humidity of each extra sensor, in %.
+    pub humidity: Channels<u8>,
+    /// `feelsLike1` to `feelsLike10`: the "feels like" temperature of each extra sensor, in °F.
+    pub feels_like: Channels<f32>,
+    /// `dewPoint1` to `dewPoint10`: the dew point of each extra sensor, in °F.
+    pub dew_point: Channels<f32>,
+    /// `batt1` to `batt10`: the battery of each extra sensor, where 1 is OK and 0 is low.
+    pub battery: Channels<u8>,
+    /// `soiltemp1f` to `soiltemp10f`: the temperature of each soil sensor, in °F.
+    pub soil_temperature: Channels<f32>,
+    /// `soilhum1` to `soilhum10`: the moisture of each soil sensor, in %.
+    pub soil_humidity: Channels<u8>,
+    /// `battsm1` to `battsm10`: the battery of each soil sensor, where 1 is OK and 0 is low.
+    pub soil_battery: Channels<u8>,
+    /// `leak1` to `leak10`: the state of each leak detector, where 0 is dry, 1 is a leak and 2 is offline.
+    pub leak: Channels<u8>,
+    /// `batleak1` to `batleak10`: the battery of each leak detector, where, unlike the other batteries, 0 is OK and 1 is low.
+    pub leak_battery: Channels<u8>,
+    /// `relay1` to `relay10`: the state of each relay, where 1 is on and 0 is off.
+    pub relay: Channels<u8>,
+}
+
+/// A private macro that lists the field names of every channel of a numbered sensor.
+macro_rules! channel_keys {
+    ($($prefix:literal $suffix:literal),* $(,)?) => {
+        &[$(
+            concat!($prefix, "1", $suffix),
+            concat!($prefix, "2", $suffix),
+            concat!($prefix, "3", $suffix),
+            concat!($prefix, "4", $suffix),
+            concat!($prefix, "5", $suffix),
+            concat!($prefix, "6", $suffix),
+            concat!($prefix, "7", $suffix),
+            concat!($prefix, "8", $suffix),
+            concat!($prefix, "9", $suffix),
+            concat!($prefix, "10", $suffix),
+        )*]
+    };
+}
+
+/// Every field name [`SensorChannels`] claims. Listing them lets the surrounding record know which fields have been accounted for.
+const CHANNEL_KEYS: &[&str] = channel_keys!(
+    "temp" "f",
+    "humidity" "",
+    "feelsLike" "",
+    "dewPoint" "",
+    "batt" "",
+    "soiltemp" "f",
+    "soilhum" "",
+    "battsm" "",
+    "leak" "",
+    "batleak" "",
+    "relay" "",
+);
+
+/// A private function that splits a numbered field name such as `temp3f` into its channel number, given the name's prefix and suffix.
+fn channel_number(key: &str, prefix: &str, suffix: &str) -> Option<usize> {
+    let number = key.strip_prefix(prefix)?.strip_suffix(suffix)?;
+
+    if number.starts_with('0') {
+        return None;
+    }
+
+    number
+        .parse()
+        .ok()
+        .filter(|channel| (1..=CHANNEL_COUNT).contains(channel))
+}
+
+impl SensorChannels {
+    /// A private function that reads one numbered field into the matching collection. Returns `false` if the key is not a numbered field.
+    fn read_field<'de, A: MapAccess<'de>>(
+        &mut self,
+        key: &str,
+        map: &mut A,
+    ) -> Result<bool, A::Error> {
+        fn read<'de, A: MapAccess<'de>, T: DeserializeOwned>(
+            channels: &mut Channels<T>,
+            channel: usize,
+            map: &mut A,
+        ) -> Result<bool, A::Error> {
+            channels.set(channel, map.next_value()?);
+            Ok(true)
+        }
+
+        // Prefixes that are the start of another prefix, such as `batt` and `battsm`, only match when the rest is a number.
+        if let Some(channel) = channel_number(key, "temp", "f") {
+            read(&mut self.temperature, channel, map)
+        } else if let Some(channel) = channel_number(key, "humidity", "") {
+            read(&mut self.humidity, channel, map)
+        } else if let Some(channel) = channel_number(key, "feelsLike", "") {
+            read(&mut self.feels_like, channel, map)
+        } else if let Some(channel) = channel_number(key, "dewPoint", "") {
+            read(&mut self.dew_point, channel, map)
+        } else if let Some(channel) = channel_number(key, "batt", "") {
+            read(&mut self.battery, channel, map)
+        } else if let Some(channel) = channel_number(key, "soiltemp", "f") {
+            read(&mut self.soil_temperature, channel, map)
+        } else if let Some(channel) = channel_number(key, "soilhum", "") {
+            read(&mut self.soil_humidity, channel, map)
+        } else if let Some(channel) = channel_number(key, "battsm", "") {
+            read(&mut self.soil_battery, channel, map)
+        } else if let Some(channel) = channel_number(key, "leak", "") {
+            read(&mut self.leak, channel, map)
+        } else if let Some(channel) = channel_number(key, "batleak", "") {
+            read(&mut self.leak_battery, channel, map)
+        } else if let Some(channel) = channel_number(key, "relay", "") {
+            read(&mut self.relay, channel, map)
+        } else {
+            Ok(false)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SensorChannels {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct SensorChannelsVisitor;
+
+        impl<'de> Visitor<'de> for SensorChannelsVisitor {
+            type Value = SensorChannels;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("numbered sensor fields")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<SensorChannels, A::Error> {
+                let mut channels = SensorChannels::default();
+
+                while let Some(key) = map.next_key::<String>()? {
+                    if !channels.read_field(&key, &mut map)? {
+                        map.next_value::<de::IgnoredAny>()?;
+                    }
+                }
+
+                Ok(channels)
+            }
+        }
+
+        deserializer.deserialize_struct("SensorChannels", CHANNEL_KEYS, SensorChannelsVisitor)
+    }
+}
+
+impl Serialize for SensorChannels {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        fn write<M: SerializeMap, T: Copy + Into<f64> + Serialize>(
+            map: &mut M,
+            channels: &Channels<T>,
+            prefix: &str,
+            suffix: &str,
+        ) -> Result<(), M::Error> {
+            for (channel, value) in channels.iter() {
+                map.serialize_entry(&format!("{prefix}{channel}{suffix}"), &JsonNumber(value))?;
+            }
+            Ok(())
+        }
+
+        let mut map = serializer.serialize_map(None)?;
+
+        write(&mut map, &self.temperature, "temp", "f")?;
+        write(&mut map, &self.humidity, "humidity", "")?;
+        write(&mut map, &self.feels_like, "feelsLike", "")?;
+        write(&mut map, &self.dew_point, "dewPoint", "")?;
+        write(&mut map, &self.battery, "batt", "")?;
+        write(&mut map, &self.soil_temperature, "soiltemp", "f")?;
+        write(&mut map, &self.soil_humidity, "soilhum", "")?;
+        write(&mut map, &self.soil_battery, "battsm", "")?;
+        write(&mut map, &self.leak, "leak", "")?;
+        write(&mut map, &self.leak_battery, "batleak", "")?;
+        write(&mut map, &self.relay, "relay", "")?;
+
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WeatherData;
+
+    #[test]
+    fn reads_leak_detector_batteries_as_channels() {
+        let data: WeatherData =
+            serde_json::from_str(r#"{ "batleak1": 0, "batleak3": 1, "leak1": 2 }"#).unwrap();
+
+        assert_eq!(data.channels.leak_battery.get(1), Some(0));
+        assert_eq!(data.channels.leak_battery.get(2), None);
+        assert_eq!(data.channels.leak_battery.get(3), Some(1));
+        assert_eq!(data.channels.leak.get(1), Some(2));
+        assert!(data.channels.battery.is_empty());
+    }
+
+    #[test]
+    fn round_trips_the_rain_gauge_and_leak_detector_batteries() {
+        let json = serde_json::json!({ "battrain": 1, "batleak2": 1 });
+        let data: WeatherData = serde_json::from_value(json.clone()).unwrap();
+
+        assert_eq!(data.battrain, Some(1));
+        assert_eq!(data.channels.leak_battery.get(2), Some(1));
+        assert_eq!(serde_json::to_value(&data).unwrap(), json);
+    }
+}
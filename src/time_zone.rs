@@ -0,0 +1,183 @@
+use chrono::{DateTime, Offset, TimeZone as _};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+use crate::{Timestamp, UtcOffset};
+
+/// An IANA time zone, such as `America/New_York`, as Ambient Weather reports in `tz`.
+///
+/// The rules of the zone, including daylight saving time, come from a copy of the IANA time zone database built into the crate, so they work the same on every system, including those without a time zone database of their own. When the database has no entry for the name, the time zone keeps its name but cannot convert times, and [`TimeZone::offset_at`] returns `None`.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{TimeZone, Timestamp};
+///
+/// let time_zone = TimeZone::named("America/New_York");
+/// let summer = Timestamp::parse("2023-07-01T12:00:00Z").unwrap();
+/// let winter = Timestamp::parse("2023-12-01T12:00:00Z").unwrap();
+///
+/// assert_eq!(time_zone.offset_at(summer).unwrap().to_string(), "-04:00");
+/// assert_eq!(time_zone.offset_at(winter).unwrap().to_string(), "-05:00");
+/// assert_eq!(TimeZone::named("Not/AZone").offset_at(summer), None);
+/// ```
+#[derive(Clone)]
+pub struct TimeZone {
+    name: String,
+    zone: Option<Tz>,
+}
+
+impl TimeZone {
+    /// Creates a time zone from its IANA name. Names without an entry in the time zone database still create a time zone, but one that cannot convert times.
+    pub fn named(name: impl Into<String>) -> Self {
+        let name = name.into();
+        let zone = name.parse().ok();
+
+        TimeZone { name, zone }
+    }
+
+    /// Returns the IANA name of the time zone.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns `true` if the rules of the time zone were found, so that it can convert times.
+    pub fn is_known(&self) -> bool {
+        self.zone.is_some()
+    }
+
+    /// Returns the offset from UTC in effect in this time zone at the given point in time. Returns `None` if the rules of the time zone are unknown.
+    pub fn offset_at(&self, timestamp: Timestamp) -> Option<UtcOffset> {
+        let zone = self.zone?;
+
+        // Points in time beyond what chrono can represent take the offset of its earliest or latest one.
+        let seconds = timestamp.as_millis().div_euclid(1000);
+        let time = DateTime::from_timestamp(seconds, 0).unwrap_or(if seconds < 0 {
+            DateTime::<chrono::Utc>::MIN_UTC
+        } else {
+            DateTime::<chrono::Utc>::MAX_UTC
+        });
+
+        Some(UtcOffset::from_seconds(
+            zone.offset_from_utc_datetime(&time.naive_utc())
+                .fix()
+                .local_minus_utc(),
+        ))
+    }
+}
+
+impl PartialEq for TimeZone {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+    }
+}
+
+impl Eq for TimeZone {}
+
+impl fmt::Debug for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("TimeZone").field(&self.name).finish()
+    }
+}
+
+impl fmt::Display for TimeZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+impl Serialize for TimeZone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+impl<'de> Deserialize<'de> for TimeZone {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(TimeZone::named)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offset(name: &str, time: &str) -> Option<String> {
+        TimeZone::named(name)
+            .offset_at(Timestamp::parse(time).unwrap())
+            .map(|offset| offset.to_string())
+    }
+
+    #[test]
+    fn follows_daylight_saving_time_changes() {
+        // Clocks in New York sprang forward at 07:00 UTC on 12 March 2023 and fell back at 06:00 UTC on 5 November 2023
+        assert_eq!(
+            offset("America/New_York", "2023-03-12T06:59:59Z").unwrap(),
+            "-05:00"
+        );
+        assert_eq!(
+            offset("America/New_York", "2023-03-12T07:00:00Z").unwrap(),
+            "-04:00"
+        );
+        assert_eq!(
+            offset("America/New_York", "2023-11-05T05:59:59Z").unwrap(),
+            "-04:00"
+        );
+        assert_eq!(
+            offset("America/New_York", "2023-11-05T06:00:00Z").unwrap(),
+            "-05:00"
+        );
+    }
+
+    #[test]
+    fn handles_zones_in_either_hemisphere_and_without_changes() {
+        assert_eq!(
+            offset("Australia/Sydney", "2023-01-01T00:00:00Z").unwrap(),
+            "+11:00"
+        );
+        assert_eq!(
+            offset("Australia/Sydney", "2023-07-01T00:00:00Z").unwrap(),
+            "+10:00"
+        );
+        assert_eq!(
+            offset("Asia/Kolkata", "2023-07-01T00:00:00Z").unwrap(),
+            "+05:30"
+        );
+        assert_eq!(offset("UTC", "2023-07-01T00:00:00Z").unwrap(), "+00:00");
+    }
+
+    #[test]
+    fn keeps_the_name_of_unknown_zones() {
+        let time_zone = TimeZone::named("Not/AZone");
+
+        assert!(!time_zone.is_known());
+        assert_eq!(time_zone.name(), "Not/AZone");
+        assert_eq!(offset("Not/AZone", "2023-07-01T00:00:00Z"), None);
+        assert_eq!(offset("../../etc/passwd", "2023-07-01T00:00:00Z"), None);
+        assert_eq!(offset("", "2023-07-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn gives_an_offset_for_any_timestamp() {
+        let time_zone = TimeZone::named("America/New_York");
+
+        assert!(time_zone
+            .offset_at(Timestamp::from_millis(i64::MIN))
+            .is_some());
+        assert!(time_zone
+            .offset_at(Timestamp::from_millis(i64::MAX))
+            .is_some());
+    }
+
+    #[test]
+    fn round_trips_through_json() {
+        let time_zone: TimeZone = serde_json::from_str(r#""Europe/London""#).unwrap();
+
+        assert!(time_zone.is_known());
+        assert_eq!(
+            serde_json::to_string(&time_zone).unwrap(),
+            r#""Europe/London""#
+        );
+    }
+}
@@ -0,0 +1,576 @@
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{
+    fmt,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::TimeZone;
+
+/// A point in time, with millisecond precision, as Ambient Weather reports them in `dateutc`, `date`, `lastRain` and `lightning_time`.
+///
+/// Timestamps deserialize from milliseconds since the Unix epoch, as in `dateutc`, or from an ISO 8601 / RFC 3339 string, as in `date`. Each field of [`WeatherData`](crate::WeatherData) serializes back into the form Ambient Weather uses for it.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{UtcOffset, WeatherData};
+///
+/// let data: WeatherData = serde_json::from_str(r#"{
+///     "dateutc": 1515436500000,
+///     "date": "2018-01-08T18:35:00.000Z",
+///     "tz": "America/New_York"
+/// }"#).unwrap();
+///
+/// assert_eq!(data.dateutc, data.date);
+/// assert_eq!(data.dateutc.unwrap().to_string(), "2018-01-08T18:35:00.000Z");
+///
+/// // The time of the observation at a fixed offset, and in the device's own time zone
+/// let eastern_standard_time = UtcOffset::from_seconds(-5 * 60 * 60);
+/// assert_eq!(data.dateutc.unwrap().to_offset(eastern_standard_time).to_string(), "2018-01-08T13:35:00.000-05:00");
+/// assert_eq!(data.local_time().unwrap().to_string(), "2018-01-08T13:35:00.000-05:00");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Creates a timestamp from milliseconds since the Unix epoch.
+    pub fn from_millis(millis: i64) -> Self {
+        Timestamp(millis)
+    }
+
+    /// Returns the number of milliseconds since the Unix epoch.
+    pub fn as_millis(&self) -> i64 {
+        self.0
+    }
+
+    /// Parses an ISO 8601 / RFC 3339 date and time with a UTC offset, such as `2018-01-08T18:35:00.000Z` or `2018-01-08T13:35:00-05:00`. Returns `None` if the text is not one.
+    pub fn parse(text: &str) -> Option<Self> {
+        let text = text.trim();
+        let (date, time) = text.split_once(['T', 't', ' '])?;
+
+        let mut date_parts = date.splitn(3, '-');
+        let year: i64 = date_parts.next()?.parse().ok()?;
+        let month: u32 = date_parts.next()?.parse().ok()?;
+        let day: u32 = date_parts.next()?.parse().ok()?;
+
+        let (time, offset_seconds) = if let Some(time) = time.strip_suffix(['Z', 'z']) {
+            (time, 0)
+        } else {
+            let sign_at = time.rfind(['+', '-'])?;
+            let (time, offset) = time.split_at(sign_at);
+            let sign = if offset.starts_with('-') { -1 } else { 1 };
+            let (hours, minutes) = offset[1..].split_once(':').unwrap_or((&offset[1..], "0"));
+            let hours: i64 = hours.parse().ok()?;
+            let minutes: i64 = minutes.parse().ok()?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            (time, sign * (hours * 3600 + minutes * 60))
+        };
+
+        let (time, fraction) = time.split_once(['.', ',']).unwrap_or((time, ""));
+        let mut time_parts = time.splitn(3, ':');
+        let hour: i64 = time_parts.next()?.parse().ok()?;
+        let minute: i64 = time_parts.next()?.parse().ok()?;
+        let second: i64 = time_parts.next().unwrap_or("0").parse().ok()?;
+
+        let millisecond: i64 = if fraction.is_empty() {
+            0
+        } else if fraction.bytes().all(|byte| byte.is_ascii_digit()) {
+            format!("{fraction:0<3}")[..3].parse().ok()?
+        } else {
+            return None;
+        };
+
+        if !(1..=12).contains(&month)
+            || day < 1
+            || day > days_in_month(year, month)
+            || hour > 23
+            || minute > 59
+            || second > 60
+        {
+            return None;
+        }
+
+        // Years far enough from the Unix epoch do not fit in a timestamp.
+        let millis = days_from_civil(year, month, day)
+            .checked_mul(86_400)?
+            .checked_add(hour * 3600 + minute * 60 + second - offset_seconds)?
+            .checked_mul(1000)?
+            .checked_add(millisecond)?;
+
+        Some(Timestamp(millis))
+    }
+
+    /// Returns the date and time in UTC.
+    pub fn to_utc(&self) -> LocalDateTime {
+        self.to_offset(UtcOffset::UTC)
+    }
+
+    /// Returns the date and time at the given offset from UTC.
+    pub fn to_offset(&self, offset: UtcOffset) -> LocalDateTime {
+        let local = self.0.saturating_add(i64::from(offset.seconds()) * 1000);
+        let days = local.div_euclid(86_400_000);
+        let millis_of_day = local.rem_euclid(86_400_000);
+        let (year, month, day) = civil_from_days(days);
+
+        LocalDateTime {
+            year,
+            month: month as u8,
+            day: day as u8,
+            hour: (millis_of_day / 3_600_000) as u8,
+            minute: (millis_of_day / 60_000 % 60) as u8,
+            second: (millis_of_day / 1000 % 60) as u8,
+            millisecond: (millis_of_day % 1000) as u16,
+            offset,
+        }
+    }
+
+    /// Returns the date and time in the given time zone. Returns `None` if the rules of the time zone are unknown, see [`TimeZone::offset_at`].
+    pub fn in_time_zone(&self, time_zone: &TimeZone) -> Option<LocalDateTime> {
+        time_zone
+            .offset_at(*self)
+            .map(|offset| self.to_offset(offset))
+    }
+
+    /// Converts the timestamp into a [`SystemTime`].
+    pub fn to_system_time(&self) -> SystemTime {
+        let magnitude = Duration::from_millis(self.0.unsigned_abs());
+
+        if self.0 >= 0 {
+            UNIX_EPOCH + magnitude
+        } else {
+            UNIX_EPOCH - magnitude
+        }
+    }
+}
+
+impl From<SystemTime> for Timestamp {
+    fn from(time: SystemTime) -> Self {
+        Timestamp(crate::historic::epoch_millis(time))
+    }
+}
+
+impl From<Timestamp> for SystemTime {
+    fn from(timestamp: Timestamp) -> Self {
+        timestamp.to_system_time()
+    }
+}
+
+impl fmt::Display for Timestamp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.to_utc().fmt(f)
+    }
+}
+
+impl Serialize for Timestamp {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_i64(self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Timestamp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TimestampVisitor;
+
+        impl<'de> de::Visitor<'de> for TimestampVisitor {
+            type Value = Timestamp;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter
+                    .write_str("milliseconds since the Unix epoch or an ISO 8601 date and time")
+            }
+
+            fn visit_i64<E: de::Error>(self, value: i64) -> Result<Timestamp, E> {
+                Ok(Timestamp(value))
+            }
+
+            fn visit_u64<E: de::Error>(self, value: u64) -> Result<Timestamp, E> {
+                i64::try_from(value)
+                    .map(Timestamp)
+                    .map_err(|_| E::invalid_value(de::Unexpected::Unsigned(value), &self))
+            }
+
+            fn visit_f64<E: de::Error>(self, value: f64) -> Result<Timestamp, E> {
+                if value.is_finite() {
+                    Ok(Timestamp(value.round() as i64))
+                } else {
+                    Err(E::invalid_value(de::Unexpected::Float(value), &self))
+                }
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Timestamp, E> {
+                Timestamp::parse(value)
+                    .ok_or_else(|| E::invalid_value(de::Unexpected::Str(value), &self))
+            }
+        }
+
+        deserializer.deserialize_any(TimestampVisitor)
+    }
+}
+
+/// Converts into a [`chrono::DateTime`] in UTC. Timestamps beyond the range of `chrono` give its earliest or latest date and time instead.
+#[cfg(feature = "chrono")]
+impl From<Timestamp> for chrono::DateTime<chrono::Utc> {
+    fn from(timestamp: Timestamp) -> Self {
+        chrono::DateTime::from_timestamp_millis(timestamp.0).unwrap_or(if timestamp.0 < 0 {
+            chrono::DateTime::<chrono::Utc>::MIN_UTC
+        } else {
+            chrono::DateTime::<chrono::Utc>::MAX_UTC
+        })
+    }
+}
+
+/// Converts from a [`chrono::DateTime`] in any time zone, dropping precision beyond milliseconds.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for Timestamp {
+    fn from(time: chrono::DateTime<Tz>) -> Self {
+        Timestamp(time.timestamp_millis())
+    }
+}
+
+/// A private module for (de)serializing optional timestamps as ISO 8601 strings, the form Ambient Weather uses for `date` and `lastRain`.
+pub(crate) mod iso8601 {
+    use super::Timestamp;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub(crate) fn serialize<S: Serializer>(
+        timestamp: &Option<Timestamp>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match timestamp {
+            Some(timestamp) => serializer.collect_str(timestamp),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Timestamp>, D::Error> {
+        Option::<Timestamp>::deserialize(deserializer)
+    }
+}
+
+/// An offset from UTC, such as `-05:00`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct UtcOffset(i32);
+
+impl UtcOffset {
+    /// The offset of UTC itself.
+    pub const UTC: UtcOffset = UtcOffset(0);
+
+    /// Creates an offset from the number of seconds ahead of UTC, which is negative west of Greenwich.
+    pub fn from_seconds(seconds: i32) -> Self {
+        UtcOffset(seconds)
+    }
+
+    /// Returns the number of seconds ahead of UTC, which is negative west of Greenwich.
+    pub fn seconds(&self) -> i32 {
+        self.0
+    }
+}
+
+impl fmt::Display for UtcOffset {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { '-' } else { '+' };
+        let seconds = self.0.unsigned_abs();
+
+        write!(f, "{sign}{:02}:{:02}", seconds / 3600, seconds / 60 % 60)
+    }
+}
+
+/// Converts from a [`chrono::FixedOffset`].
+#[cfg(feature = "chrono")]
+impl From<chrono::FixedOffset> for UtcOffset {
+    fn from(offset: chrono::FixedOffset) -> Self {
+        UtcOffset(offset.local_minus_utc())
+    }
+}
+
+/// A calendar date and wall clock time at some offset from UTC, as returned by [`Timestamp::to_offset`] and [`Timestamp::in_time_zone`].
+///
+/// It displays and serializes as an RFC 3339 date and time, such as `2018-01-08T13:35:00.000-05:00`, or with a `Z` in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LocalDateTime {
+    /// The year.
+    pub year: i64,
+    /// The month, from 1 to 12.
+    pub month: u8,
+    /// The day of the month, from 1 to 31.
+    pub day: u8,
+    /// The hour, from 0 to 23.
+    pub hour: u8,
+    /// The minute, from 0 to 59.
+    pub minute: u8,
+    /// The second, from 0 to 59.
+    pub second: u8,
+    /// The millisecond, from 0 to 999.
+    pub millisecond: u16,
+    /// The offset from UTC this date and time is at.
+    pub offset: UtcOffset,
+}
+
+impl LocalDateTime {
+    /// Returns the point in time this date and time refers to. Dates too far from the Unix epoch for a [`Timestamp`] give the earliest or latest one instead.
+    pub fn timestamp(&self) -> Timestamp {
+        let seconds = i128::from(days_from_civil(
+            self.year,
+            self.month.into(),
+            self.day.into(),
+        )) * 86_400
+            + i128::from(self.hour) * 3600
+            + i128::from(self.minute) * 60
+            + i128::from(self.second)
+            - i128::from(self.offset.seconds());
+        let millis = seconds * 1000 + i128::from(self.millisecond);
+
+        Timestamp(i64::try_from(millis).unwrap_or(if millis < 0 { i64::MIN } else { i64::MAX }))
+    }
+
+    /// Returns the day of the week, where 0 is Sunday and 6 is Saturday.
+    pub fn weekday(&self) -> u8 {
+        weekday(days_from_civil(
+            self.year,
+            self.month.into(),
+            self.day.into(),
+        ))
+    }
+}
+
+impl fmt::Display for LocalDateTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}",
+            self.year, self.month, self.day, self.hour, self.minute, self.second, self.millisecond
+        )?;
+
+        if self.offset == UtcOffset::UTC {
+            f.write_str("Z")
+        } else {
+            self.offset.fmt(f)
+        }
+    }
+}
+
+impl Serialize for LocalDateTime {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Converts into a [`chrono::DateTime`] at the same offset. Offsets of a day or more, which `chrono` cannot represent, give the same point in time in UTC instead.
+#[cfg(feature = "chrono")]
+impl From<LocalDateTime> for chrono::DateTime<chrono::FixedOffset> {
+    fn from(local_time: LocalDateTime) -> Self {
+        let offset = chrono::FixedOffset::east_opt(local_time.offset.seconds())
+            .unwrap_or(chrono::FixedOffset::east_opt(0).expect("UTC is a valid offset"));
+
+        chrono::DateTime::<chrono::Utc>::from(local_time.timestamp()).with_timezone(&offset)
+    }
+}
+
+/// Converts from a [`chrono::DateTime`] in any time zone, keeping its offset from UTC and dropping precision beyond milliseconds.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> From<chrono::DateTime<Tz>> for LocalDateTime {
+    fn from(time: chrono::DateTime<Tz>) -> Self {
+        use chrono::Offset;
+
+        let offset = UtcOffset::from(time.offset().fix());
+        Timestamp::from(time).to_offset(offset)
+    }
+}
+
+/// A private function that returns the number of days from 1970-01-01 to a date of the proleptic Gregorian calendar. Dates too far away for an `i64` give `i64::MIN` or `i64::MAX` instead.
+pub(crate) fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    // The arithmetic is done in `i128`, which cannot overflow for any `i64` year.
+    let year = i128::from(year) - i128::from(month <= 2);
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = i128::from(month);
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i128::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    let days = era * 146_097 + day_of_era - 719_468;
+    i64::try_from(days).unwrap_or(if days < 0 { i64::MIN } else { i64::MAX })
+}
+
+/// A private function that returns the date of the proleptic Gregorian calendar that is a number of days from 1970-01-01, as `(year, month, day)`.
+pub(crate) fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month, day)
+}
+
+/// A private function that returns the day of the week of a number of days from 1970-01-01, where 0 is Sunday.
+pub(crate) fn weekday(days: i64) -> u8 {
+    // 1970-01-01 was a Thursday.
+    (days + 4).rem_euclid(7) as u8
+}
+
+/// A private function that checks whether a year of the Gregorian calendar is a leap year.
+pub(crate) fn is_leap_year(year: i64) -> bool {
+    year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
+}
+
+/// A private function that returns the number of days in a month of the Gregorian calendar.
+pub(crate) fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_utc_and_fixed_offsets() {
+        let utc = Timestamp::parse("2018-01-08T18:35:00.000Z").unwrap();
+        assert_eq!(utc.as_millis(), 1_515_436_500_000);
+
+        assert_eq!(Timestamp::parse("2018-01-08T13:35:00-05:00"), Some(utc));
+        assert_eq!(Timestamp::parse("2018-01-09T00:05:00+05:30"), Some(utc));
+        assert_eq!(Timestamp::parse("2018-01-08 18:35z"), Some(utc));
+        assert_eq!(
+            Timestamp::parse("2018-01-08T18:35:00.1234Z")
+                .unwrap()
+                .as_millis(),
+            1_515_436_500_123
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_dates_and_offsets() {
+        for text in [
+            "2018-02-29T00:00:00Z",
+            "2018-13-01T00:00:00Z",
+            "2018-01-08T24:00:00Z",
+            "2018-01-08T18:35:00",
+            "2018-01-08T18:35:00+24:00",
+            "2018-01-08T18:35:00-05:60",
+            "2018-01-08T18:35:00.5xZ",
+            "not a date",
+        ] {
+            assert_eq!(Timestamp::parse(text), None, "{text}");
+        }
+    }
+
+    #[test]
+    fn rejects_years_beyond_a_timestamp_without_overflowing() {
+        assert_eq!(
+            Timestamp::parse("9223372036854775807-12-31T23:59:59Z"),
+            None
+        );
+        assert_eq!(
+            Timestamp::parse("-9223372036854775808-01-01T00:00:00Z"),
+            None
+        );
+        assert_eq!(Timestamp::parse("300000000-01-01T00:00:00Z"), None);
+        assert!(Timestamp::parse("200000000-01-01T00:00:00Z").is_some());
+    }
+
+    #[test]
+    fn shows_times_at_fixed_offsets() {
+        let time = Timestamp::from_millis(1_515_436_500_000);
+
+        assert_eq!(time.to_utc().to_string(), "2018-01-08T18:35:00.000Z");
+        assert_eq!(
+            time.to_offset(UtcOffset::from_seconds(-5 * 3600))
+                .to_string(),
+            "2018-01-08T13:35:00.000-05:00"
+        );
+        assert_eq!(
+            time.to_offset(UtcOffset::from_seconds(5 * 3600 + 45 * 60))
+                .to_string(),
+            "2018-01-09T00:20:00.000+05:45"
+        );
+        assert_eq!(
+            Timestamp::from_millis(-1).to_utc().to_string(),
+            "1969-12-31T23:59:59.999Z"
+        );
+    }
+
+    #[test]
+    fn local_times_round_trip() {
+        for millis in [
+            0,
+            -1,
+            951_782_400_000,
+            1_515_436_500_123,
+            -62_135_596_800_000,
+        ] {
+            for offset in [0, -5 * 3600, 14 * 3600] {
+                let time = Timestamp::from_millis(millis);
+                let local_time = time.to_offset(UtcOffset::from_seconds(offset));
+                assert_eq!(local_time.timestamp(), time);
+                assert_eq!(Timestamp::parse(&local_time.to_string()), Some(time));
+            }
+        }
+    }
+
+    #[test]
+    fn saturates_local_times_beyond_a_timestamp() {
+        let latest = Timestamp::from_millis(i64::MAX);
+        let local_time = latest.to_utc();
+
+        assert_eq!(local_time.timestamp(), latest);
+        for (year, expected) in [(i64::MAX, i64::MAX), (i64::MIN, i64::MIN)] {
+            let local_time = LocalDateTime {
+                year,
+                offset: UtcOffset::from_seconds(-3600),
+                ..local_time
+            };
+            assert_eq!(local_time.timestamp(), Timestamp::from_millis(expected));
+        }
+    }
+
+    #[test]
+    fn counts_days_across_calendar_edges() {
+        assert_eq!(days_from_civil(1970, 1, 1), 0);
+        assert_eq!(days_from_civil(2000, 3, 1), 11_017);
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(-1), (1969, 12, 31));
+        assert_eq!(days_from_civil(i64::MAX, 12, 31), i64::MAX);
+        assert_eq!(days_from_civil(i64::MIN, 1, 1), i64::MIN);
+        assert_eq!(weekday(0), 4);
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn converts_to_and_from_chrono() {
+        use chrono::{DateTime, FixedOffset, Utc};
+
+        let time = Timestamp::from_millis(1_515_436_500_123);
+        let utc = DateTime::<Utc>::from(time);
+        assert_eq!(utc.to_rfc3339(), "2018-01-08T18:35:00.123+00:00");
+        assert_eq!(Timestamp::from(utc), time);
+
+        let eastern = time.to_offset(UtcOffset::from_seconds(-5 * 3600));
+        let fixed = DateTime::<FixedOffset>::from(eastern);
+        assert_eq!(fixed.to_rfc3339(), "2018-01-08T13:35:00.123-05:00");
+        assert_eq!(LocalDateTime::from(fixed), eastern);
+
+        assert_eq!(
+            DateTime::<Utc>::from(Timestamp::from_millis(i64::MAX)),
+            DateTime::<Utc>::MAX_UTC
+        );
+    }
+}
@@ -0,0 +1,455 @@
+use serde::{ser::SerializeMap, Serialize, Serializer};
+use serde_json::{Map, Value};
+
+use crate::{
+    Channels, Length, LengthUnit, Pressure, PressureUnit, Speed, SpeedUnit, Temperature,
+    TemperatureUnit, WeatherData,
+};
+
+/// The units each kind of measurement is expressed in by [`WeatherData::to_unit_system`].
+///
+/// Solar radiation, particulate matter and CO₂ are left in the units Ambient Weather reports them in, which are already metric.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Units {
+    /// The unit of temperatures, including dew points and "feels like" temperatures.
+    pub temperature: TemperatureUnit,
+    /// The unit of barometric pressures.
+    pub pressure: PressureUnit,
+    /// The unit of wind speeds and gusts.
+    pub speed: SpeedUnit,
+    /// The unit of rain amounts.
+    pub rain: LengthUnit,
+    /// The unit of lightning distances.
+    pub distance: LengthUnit,
+}
+
+/// A set of units to convert a [`WeatherData`] record to with [`WeatherData::to_unit_system`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitSystem {
+    /// °C, hPa, km/h, mm and km.
+    Metric,
+    /// °F, inHg, mph, in and mi. These are the units Ambient Weather reports in, except for lightning distances, which it reports in km and which are converted to miles.
+    Imperial,
+    /// °C, hPa, mph, mm and mi, as commonly used in the United Kingdom.
+    UkMixed,
+    /// Any other combination of units, such as wind speeds in m/s.
+    Custom(Units),
+}
+
+impl UnitSystem {
+    /// Returns the units this system expresses each kind of measurement in.
+    pub fn units(&self) -> Units {
+        match self {
+            UnitSystem::Metric => Units {
+                temperature: TemperatureUnit::Celsius,
+                pressure: PressureUnit::Hectopascals,
+                speed: SpeedUnit::KilometresPerHour,
+                rain: LengthUnit::Millimetres,
+                distance: LengthUnit::Kilometres,
+            },
+            UnitSystem::Imperial => Units {
+                temperature: TemperatureUnit::Fahrenheit,
+                pressure: PressureUnit::InchesOfMercury,
+                speed: SpeedUnit::MilesPerHour,
+                rain: LengthUnit::Inches,
+                distance: LengthUnit::Miles,
+            },
+            UnitSystem::UkMixed => Units {
+                temperature: TemperatureUnit::Celsius,
+                pressure: PressureUnit::Hectopascals,
+                speed: SpeedUnit::MilesPerHour,
+                rain: LengthUnit::Millimetres,
+                distance: LengthUnit::Miles,
+            },
+            UnitSystem::Custom(units) => *units,
+        }
+    }
+}
+
+/// A [`WeatherData`] record with its measurements converted to a [`UnitSystem`], as returned by [`WeatherData::to_unit_system`].
+///
+/// When serialized, each converted field is named after what it measures with the symbol of its unit appended, such as `temperature_c`, `wind_speed_kmh`, `relative_pressure_hpa` or `daily_rain_mm`, and the numbered channels become `temperature1_c`, `soil_temperature1_c` and so on. All other fields keep their Ambient Weather names and values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConvertedWeatherData {
+    /// The units the measurements below are in.
+    pub units: Units,
+    /// The outdoor temperature, from `tempf`.
+    pub temperature: Option<f32>,
+    /// The indoor temperature, from `tempinf`.
+    pub indoor_temperature: Option<f32>,
+    /// The outdoor "feels like" temperature, from `feelsLike`.
+    pub feels_like: Option<f32>,
+    /// The indoor "feels like" temperature, from `feelsLikein`.
+    pub indoor_feels_like: Option<f32>,
+    /// The outdoor dew point, from `dewPoint`.
+    pub dew_point: Option<f32>,
+    /// The indoor dew point, from `dewPointin`.
+    pub indoor_dew_point: Option<f32>,
+    /// The temperature measured by the indoor particulate matter sensor, from `pm_in_temp`.
+    pub pm_in_temperature: Option<f32>,
+    /// The temperature measured by the AQIN sensor, from `pm_in_temp_aqin`.
+    pub pm_in_temperature_aqin: Option<f32>,
+    /// The relative (sea level) pressure, from `baromrelin`.
+    pub relative_pressure: Option<f32>,
+    /// The absolute (station) pressure, from `baromabsin`.
+    pub absolute_pressure: Option<f32>,
+    /// The instantaneous wind speed, from `windspeedmph`.
+    pub wind_speed: Option<f32>,
+    /// The largest wind gust of the last 10 minutes, from `windgustmph`.
+    pub wind_gust: Option<f32>,
+    /// The largest wind gust of the day, from `maxdailygust`.
+    pub max_daily_gust: Option<f32>,
+    /// The average wind speed over 2 minutes, from `windspdmph_avg2m`.
+    pub wind_speed_avg_2m: Option<f32>,
+    /// The average wind speed over 10 minutes, from `windspdmph_avg10m`.
+    pub wind_speed_avg_10m: Option<f32>,
+    /// The rain of the current hour, from `hourlyrainin`.
+    pub hourly_rain: Option<f32>,
+    /// The rain of the current day, from `dailyrainin`.
+    pub daily_rain: Option<f32>,
+    /// The rain of the last 24 hours, from `24hourrainin`.
+    pub rain_24h: Option<f32>,
+    /// The rain of the current week, from `weeklyrainin`.
+    pub weekly_rain: Option<f32>,
+    /// The rain of the current month, from `monthlyrainin`.
+    pub monthly_rain: Option<f32>,
+    /// The rain of the current year, from `yearlyrainin`.
+    pub yearly_rain: Option<f32>,
+    /// The rain of the current rain event, from `eventrainin`.
+    pub event_rain: Option<f32>,
+    /// The rain since the rain gauge was last reset, from `totalrainin`.
+    pub total_rain: Option<f32>,
+    /// The distance to the last lightning strike, from `lightning_distance`.
+    pub lightning_distance: Option<f32>,
+    /// The temperature of each extra sensor, from `temp1f` to `temp10f`.
+    pub channel_temperature: Channels<f32>,
+    /// The "feels like" temperature of each extra sensor, from `feelsLike1` to `feelsLike10`.
+    pub channel_feels_like: Channels<f32>,
+    /// The dew point of each extra sensor, from `dewPoint1` to `dewPoint10`.
+    pub channel_dew_point: Channels<f32>,
+    /// The temperature of each soil sensor, from `soiltemp1f` to `soiltemp10f`.
+    pub soil_temperature: Channels<f32>,
+    /// Every other field of the record, which needs no conversion, keyed by its Ambient Weather name.
+    pub other: Map<String, Value>,
+}
+
+impl WeatherData {
+    /// Converts the measurements of this record to the units of `system`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{UnitSystem, WeatherData};
+    ///
+    /// let data: WeatherData = serde_json::from_str(
+    ///     r#"{ "tempf": 50.0, "windspeedmph": 10.0, "dailyrainin": 0.5, "humidity": 45 }"#,
+    /// ).unwrap();
+    ///
+    /// let metric = data.to_unit_system(UnitSystem::Metric);
+    /// assert_eq!(metric.temperature, Some(10.0));
+    /// assert_eq!(metric.daily_rain, Some(12.7));
+    ///
+    /// let json = serde_json::to_value(&metric).unwrap();
+    /// assert_eq!(json["temperature_c"], 10.0);
+    /// assert!(json.get("daily_rain_mm").is_some());
+    /// assert_eq!(json["humidity"], 45);
+    /// assert!(json.get("wind_speed_kmh").is_some());
+    /// assert!(json.get("tempf").is_none());
+    /// ```
+    pub fn to_unit_system(&self, system: UnitSystem) -> ConvertedWeatherData {
+        let units = system.units();
+        let mut rest = self.clone();
+
+        let temperature = |value: Option<f32>| {
+            value.map(|value| Temperature::from_fahrenheit(value).in_unit(units.temperature))
+        };
+        let pressure = |value: Option<f32>| {
+            value.map(|value| Pressure::from_inches_of_mercury(value).in_unit(units.pressure))
+        };
+        let speed = |value: Option<f32>| {
+            value.map(|value| Speed::from_miles_per_hour(value).in_unit(units.speed))
+        };
+        let rain =
+            |value: Option<f32>| value.map(|value| Length::from_inches(value).in_unit(units.rain));
+        let distance = |value: Option<f32>| {
+            value.map(|value| Length::from_kilometres(value).in_unit(units.distance))
+        };
+        let temperature_channels = |channels: &mut Channels<f32>| {
+            let mut converted = Channels::default();
+            for (channel, value) in std::mem::take(channels).iter() {
+                converted.set(channel, temperature(Some(value)));
+            }
+            converted
+        };
+
+        let converted = ConvertedWeatherData {
+            units,
+            temperature: temperature(rest.tempf.take()),
+            indoor_temperature: temperature(rest.tempinf.take()),
+            feels_like: temperature(rest.feels_like.take()),
+            indoor_feels_like: temperature(rest.feels_like_in.take()),
+            dew_point: temperature(rest.dew_point.take()),
+            indoor_dew_point: temperature(rest.dew_point_in.take()),
+            pm_in_temperature: temperature(rest.pm_in_temp.take()),
+            pm_in_temperature_aqin: temperature(rest.pm_in_temp_aqin.take()),
+            relative_pressure: pressure(rest.baromrelin.take()),
+            absolute_pressure: pressure(rest.baromabsin.take()),
+            wind_speed: speed(rest.windspeedmph.take()),
+            wind_gust: speed(rest.windgustmph.take()),
+            max_daily_gust: speed(rest.maxdailygust.take()),
+            wind_speed_avg_2m: speed(rest.windspdmph_avg2m.take()),
+            wind_speed_avg_10m: speed(rest.windspdmph_avg10m.take()),
+            hourly_rain: rain(rest.hourlyrainin.take()),
+            daily_rain: rain(rest.dailyrainin.take()),
+            rain_24h: rain(rest.hour24rainin.take()),
+            weekly_rain: rain(rest.weeklyrainin.take()),
+            monthly_rain: rain(rest.monthlyrainin.take()),
+            yearly_rain: rain(rest.yearlyrainin.take()),
+            event_rain: rain(rest.eventrainin.take()),
+            total_rain: rain(rest.totalrainin.take()),
+            lightning_distance: distance(rest.lightning_distance.take()),
+            channel_temperature: temperature_channels(&mut rest.channels.temperature),
+            channel_feels_like: temperature_channels(&mut rest.channels.feels_like),
+            channel_dew_point: temperature_channels(&mut rest.channels.dew_point),
+            soil_temperature: temperature_channels(&mut rest.channels.soil_temperature),
+            other: Map::new(),
+        };
+
+        // Only the fields that need no conversion are left.
+        let other = match serde_json::to_value(&rest) {
+            Ok(Value::Object(other)) => other,
+            _ => Map::new(),
+        };
+
+        ConvertedWeatherData { other, ..converted }
+    }
+}
+
+impl Serialize for ConvertedWeatherData {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        fn write<M: SerializeMap>(
+            map: &mut M,
+            name: &str,
+            suffix: &str,
+            value: Option<f32>,
+        ) -> Result<(), M::Error> {
+            match value {
+                Some(value) => map.serialize_entry(&format!("{name}_{suffix}"), &value),
+                None => Ok(()),
+            }
+        }
+
+        fn write_channels<M: SerializeMap>(
+            map: &mut M,
+            name: &str,
+            suffix: &str,
+            channels: &Channels<f32>,
+        ) -> Result<(), M::Error> {
+            for (channel, value) in channels.iter() {
+                map.serialize_entry(&format!("{name}{channel}_{suffix}"), &value)?;
+            }
+            Ok(())
+        }
+
+        let temperature = self.units.temperature.suffix();
+        let pressure = self.units.pressure.suffix();
+        let speed = self.units.speed.suffix();
+        let rain = self.units.rain.suffix();
+        let distance = self.units.distance.suffix();
+
+        let mut map = serializer.serialize_map(None)?;
+
+        write(&mut map, "temperature", temperature, self.temperature)?;
+        write(
+            &mut map,
+            "indoor_temperature",
+            temperature,
+            self.indoor_temperature,
+        )?;
+        write(&mut map, "feels_like", temperature, self.feels_like)?;
+        write(
+            &mut map,
+            "indoor_feels_like",
+            temperature,
+            self.indoor_feels_like,
+        )?;
+        write(&mut map, "dew_point", temperature, self.dew_point)?;
+        write(
+            &mut map,
+            "indoor_dew_point",
+            temperature,
+            self.indoor_dew_point,
+        )?;
+        write(
+            &mut map,
+            "pm_in_temperature",
+            temperature,
+            self.pm_in_temperature,
+        )?;
+        write(
+            &mut map,
+            "pm_in_temperature_aqin",
+            temperature,
+            self.pm_in_temperature_aqin,
+        )?;
+        write(
+            &mut map,
+            "relative_pressure",
+            pressure,
+            self.relative_pressure,
+        )?;
+        write(
+            &mut map,
+            "absolute_pressure",
+            pressure,
+            self.absolute_pressure,
+        )?;
+        write(&mut map, "wind_speed", speed, self.wind_speed)?;
+        write(&mut map, "wind_gust", speed, self.wind_gust)?;
+        write(&mut map, "max_daily_gust", speed, self.max_daily_gust)?;
+        write(&mut map, "wind_speed_avg_2m", speed, self.wind_speed_avg_2m)?;
+        write(
+            &mut map,
+            "wind_speed_avg_10m",
+            speed,
+            self.wind_speed_avg_10m,
+        )?;
+        write(&mut map, "hourly_rain", rain, self.hourly_rain)?;
+        write(&mut map, "daily_rain", rain, self.daily_rain)?;
+        write(&mut map, "rain_24h", rain, self.rain_24h)?;
+        write(&mut map, "weekly_rain", rain, self.weekly_rain)?;
+        write(&mut map, "monthly_rain", rain, self.monthly_rain)?;
+        write(&mut map, "yearly_rain", rain, self.yearly_rain)?;
+        write(&mut map, "event_rain", rain, self.event_rain)?;
+        write(&mut map, "total_rain", rain, self.total_rain)?;
+        write(
+            &mut map,
+            "lightning_distance",
+            distance,
+            self.lightning_distance,
+        )?;
+        write_channels(
+            &mut map,
+            "temperature",
+            temperature,
+            &self.channel_temperature,
+        )?;
+        write_channels(
+            &mut map,
+            "feels_like",
+            temperature,
+            &self.channel_feels_like,
+        )?;
+        write_channels(&mut map, "dew_point", temperature, &self.channel_dew_point)?;
+        write_channels(
+            &mut map,
+            "soil_temperature",
+            temperature,
+            &self.soil_temperature,
+        )?;
+
+        for (key, value) in &self.other {
+            map.serialize_entry(key, value)?;
+        }
+
+        map.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record() -> WeatherData {
+        serde_json::from_value(serde_json::json!({
+            "tempf": 50.0,
+            "temp1f": 68.0,
+            "baromrelin": 29.92,
+            "windspeedmph": 10.0,
+            "dailyrainin": 0.5,
+            "lightning_distance": 16.09344,
+            "humidity": 45,
+        }))
+        .unwrap()
+    }
+
+    fn rounded(value: Option<f32>, decimals: i32) -> Option<f32> {
+        let scale = 10f32.powi(decimals);
+        value.map(|value| (value * scale).round() / scale)
+    }
+
+    #[test]
+    fn converts_only_lightning_distances_to_imperial() {
+        let imperial = record().to_unit_system(UnitSystem::Imperial);
+
+        assert_eq!(imperial.temperature, Some(50.0));
+        assert_eq!(imperial.relative_pressure, Some(29.92));
+        assert_eq!(imperial.wind_speed, Some(10.0));
+        assert_eq!(imperial.daily_rain, Some(0.5));
+        assert!((imperial.lightning_distance.unwrap() - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn converts_everything_to_metric() {
+        let metric = record().to_unit_system(UnitSystem::Metric);
+
+        assert_eq!(metric.temperature, Some(10.0));
+        assert_eq!(metric.channel_temperature.get(1), Some(20.0));
+        assert_eq!(rounded(metric.relative_pressure, 1), Some(1013.2));
+        assert_eq!(rounded(metric.wind_speed, 2), Some(16.09));
+        assert_eq!(rounded(metric.daily_rain, 2), Some(12.7));
+        assert_eq!(rounded(metric.lightning_distance, 4), Some(16.0934));
+
+        let json = serde_json::to_value(&metric).unwrap();
+        assert_eq!(json["temperature_c"], 10.0);
+        assert_eq!(json["temperature1_c"], 20.0);
+        assert!(json.get("relative_pressure_hpa").is_some());
+        assert!(json.get("wind_speed_kmh").is_some());
+        assert!(json.get("daily_rain_mm").is_some());
+        assert!(json.get("lightning_distance_km").is_some());
+        assert_eq!(json["humidity"], 45);
+    }
+
+    #[test]
+    fn keeps_speeds_and_distances_imperial_in_uk_mixed() {
+        let uk = record().to_unit_system(UnitSystem::UkMixed);
+
+        assert_eq!(uk.temperature, Some(10.0));
+        assert_eq!(rounded(uk.relative_pressure, 1), Some(1013.2));
+        assert_eq!(uk.wind_speed, Some(10.0));
+        assert_eq!(rounded(uk.daily_rain, 2), Some(12.7));
+        assert_eq!(rounded(uk.lightning_distance, 4), Some(10.0));
+
+        let json = serde_json::to_value(&uk).unwrap();
+        assert!(json.get("wind_speed_mph").is_some());
+        assert!(json.get("lightning_distance_mi").is_some());
+    }
+
+    #[test]
+    fn converts_to_custom_units() {
+        let units = Units {
+            temperature: TemperatureUnit::Kelvin,
+            pressure: PressureUnit::Kilopascals,
+            speed: SpeedUnit::MetresPerSecond,
+            rain: LengthUnit::Centimetres,
+            distance: LengthUnit::Metres,
+        };
+        assert_eq!(UnitSystem::Custom(units).units(), units);
+
+        let custom = record().to_unit_system(UnitSystem::Custom(units));
+
+        assert_eq!(rounded(custom.temperature, 2), Some(283.15));
+        assert_eq!(rounded(custom.relative_pressure, 2), Some(101.32));
+        assert_eq!(rounded(custom.wind_speed, 3), Some(4.47));
+        assert_eq!(rounded(custom.daily_rain, 2), Some(1.27));
+        assert_eq!(rounded(custom.lightning_distance, 0), Some(16093.0));
+
+        let json = serde_json::to_value(&custom).unwrap();
+        assert!(json.get("temperature_k").is_some());
+        assert!(json.get("temperature1_k").is_some());
+        assert!(json.get("relative_pressure_kpa").is_some());
+        assert!(json.get("wind_speed_ms").is_some());
+        assert!(json.get("daily_rain_cm").is_some());
+        assert!(json.get("lightning_distance_m").is_some());
+    }
+}
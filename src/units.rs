@@ -0,0 +1,713 @@
+/// The molar mass of carbon dioxide, in g/mol, for converting CO₂ readings between ppm and µg/m³ with [`Concentration`].
+pub const CO2_MOLAR_MASS: f32 = 44.01;
+
+/// The volume of one mole of an ideal gas at 25 °C and 1 atm, in litres, which Ambient Weather's air quality sensors are referenced to.
+const MOLAR_VOLUME: f64 = 24.45;
+
+/// A temperature.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::Temperature;
+///
+/// let temperature = Temperature::from_fahrenheit(212.0);
+///
+/// assert_eq!(temperature.celsius(), 100.0);
+/// assert_eq!(Temperature::from_celsius(-40.0).fahrenheit(), -40.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Temperature {
+    celsius: f64,
+}
+
+impl Temperature {
+    /// Creates a temperature from degrees Fahrenheit, the unit Ambient Weather reports in.
+    pub fn from_fahrenheit(fahrenheit: f32) -> Self {
+        Temperature {
+            celsius: (f64::from(fahrenheit) - 32.0) * 5.0 / 9.0,
+        }
+    }
+
+    /// Creates a temperature from degrees Celsius.
+    pub fn from_celsius(celsius: f32) -> Self {
+        Temperature {
+            celsius: f64::from(celsius),
+        }
+    }
+
+    /// Creates a temperature from kelvins.
+    pub fn from_kelvin(kelvin: f32) -> Self {
+        Temperature {
+            celsius: f64::from(kelvin) - 273.15,
+        }
+    }
+
+    /// Returns the temperature in degrees Fahrenheit.
+    pub fn fahrenheit(&self) -> f32 {
+        (self.celsius * 9.0 / 5.0 + 32.0) as f32
+    }
+
+    /// Returns the temperature in degrees Celsius.
+    pub fn celsius(&self) -> f32 {
+        self.celsius as f32
+    }
+
+    /// Returns the temperature in kelvins.
+    pub fn kelvin(&self) -> f32 {
+        (self.celsius + 273.15) as f32
+    }
+
+    /// Returns the temperature in the given unit.
+    pub fn in_unit(&self, unit: TemperatureUnit) -> f32 {
+        match unit {
+            TemperatureUnit::Fahrenheit => self.fahrenheit(),
+            TemperatureUnit::Celsius => self.celsius(),
+            TemperatureUnit::Kelvin => self.kelvin(),
+        }
+    }
+}
+
+/// An atmospheric pressure.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::Pressure;
+///
+/// let pressure = Pressure::from_inches_of_mercury(29.92);
+///
+/// assert_eq!(pressure.hectopascals().round(), 1013.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Pressure {
+    hectopascals: f64,
+}
+
+impl Pressure {
+    /// The number of hectopascals in one inch of mercury.
+    const HECTOPASCALS_PER_INCH_OF_MERCURY: f64 = 33.863_886_666_7;
+
+    /// The number of hectopascals in one millimetre of mercury.
+    const HECTOPASCALS_PER_MILLIMETRE_OF_MERCURY: f64 = 1.333_223_874_15;
+
+    /// Creates a pressure from inches of mercury, the unit Ambient Weather reports in.
+    pub fn from_inches_of_mercury(inches_of_mercury: f32) -> Self {
+        Pressure {
+            hectopascals: f64::from(inches_of_mercury) * Self::HECTOPASCALS_PER_INCH_OF_MERCURY,
+        }
+    }
+
+    /// Creates a pressure from hectopascals, which are the same as millibars.
+    pub fn from_hectopascals(hectopascals: f32) -> Self {
+        Pressure {
+            hectopascals: f64::from(hectopascals),
+        }
+    }
+
+    /// Creates a pressure from kilopascals.
+    pub fn from_kilopascals(kilopascals: f32) -> Self {
+        Pressure {
+            hectopascals: f64::from(kilopascals) * 10.0,
+        }
+    }
+
+    /// Creates a pressure from millimetres of mercury.
+    pub fn from_millimetres_of_mercury(millimetres_of_mercury: f32) -> Self {
+        Pressure {
+            hectopascals: f64::from(millimetres_of_mercury)
+                * Self::HECTOPASCALS_PER_MILLIMETRE_OF_MERCURY,
+        }
+    }
+
+    /// Returns the pressure in inches of mercury.
+    pub fn inches_of_mercury(&self) -> f32 {
+        (self.hectopascals / Self::HECTOPASCALS_PER_INCH_OF_MERCURY) as f32
+    }
+
+    /// Returns the pressure in hectopascals.
+    pub fn hectopascals(&self) -> f32 {
+        self.hectopascals as f32
+    }
+
+    /// Returns the pressure in millibars, which are the same as hectopascals.
+    pub fn millibars(&self) -> f32 {
+        self.hectopascals()
+    }
+
+    /// Returns the pressure in kilopascals.
+    pub fn kilopascals(&self) -> f32 {
+        (self.hectopascals / 10.0) as f32
+    }
+
+    /// Returns the pressure in millimetres of mercury.
+    pub fn millimetres_of_mercury(&self) -> f32 {
+        (self.hectopascals / Self::HECTOPASCALS_PER_MILLIMETRE_OF_MERCURY) as f32
+    }
+
+    /// Returns the pressure in the given unit.
+    pub fn in_unit(&self, unit: PressureUnit) -> f32 {
+        match unit {
+            PressureUnit::InchesOfMercury => self.inches_of_mercury(),
+            PressureUnit::Hectopascals => self.hectopascals(),
+            PressureUnit::Kilopascals => self.kilopascals(),
+            PressureUnit::MillimetresOfMercury => self.millimetres_of_mercury(),
+        }
+    }
+}
+
+/// A speed, such as that of the wind.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::Speed;
+///
+/// let speed = Speed::from_kilometres_per_hour(36.0);
+///
+/// assert_eq!(speed.metres_per_second(), 10.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Speed {
+    metres_per_second: f64,
+}
+
+impl Speed {
+    /// The number of metres per second in one mile per hour.
+    const METRES_PER_SECOND_PER_MILE_PER_HOUR: f64 = 0.447_04;
+
+    /// The number of metres per second in one knot.
+    const METRES_PER_SECOND_PER_KNOT: f64 = 1852.0 / 3600.0;
+
+    /// Creates a speed from miles per hour, the unit Ambient Weather reports in.
+    pub fn from_miles_per_hour(miles_per_hour: f32) -> Self {
+        Speed {
+            metres_per_second: f64::from(miles_per_hour)
+                * Self::METRES_PER_SECOND_PER_MILE_PER_HOUR,
+        }
+    }
+
+    /// Creates a speed from kilometres per hour.
+    pub fn from_kilometres_per_hour(kilometres_per_hour: f32) -> Self {
+        Speed {
+            metres_per_second: f64::from(kilometres_per_hour) / 3.6,
+        }
+    }
+
+    /// Creates a speed from metres per second.
+    pub fn from_metres_per_second(metres_per_second: f32) -> Self {
+        Speed {
+            metres_per_second: f64::from(metres_per_second),
+        }
+    }
+
+    /// Creates a speed from knots.
+    pub fn from_knots(knots: f32) -> Self {
+        Speed {
+            metres_per_second: f64::from(knots) * Self::METRES_PER_SECOND_PER_KNOT,
+        }
+    }
+
+    /// Returns the speed in miles per hour.
+    pub fn miles_per_hour(&self) -> f32 {
+        (self.metres_per_second / Self::METRES_PER_SECOND_PER_MILE_PER_HOUR) as f32
+    }
+
+    /// Returns the speed in kilometres per hour.
+    pub fn kilometres_per_hour(&self) -> f32 {
+        (self.metres_per_second * 3.6) as f32
+    }
+
+    /// Returns the speed in metres per second.
+    pub fn metres_per_second(&self) -> f32 {
+        self.metres_per_second as f32
+    }
+
+    /// Returns the speed in knots.
+    pub fn knots(&self) -> f32 {
+        (self.metres_per_second / Self::METRES_PER_SECOND_PER_KNOT) as f32
+    }
+
+    /// Returns the speed in the given unit.
+    pub fn in_unit(&self, unit: SpeedUnit) -> f32 {
+        match unit {
+            SpeedUnit::MilesPerHour => self.miles_per_hour(),
+            SpeedUnit::KilometresPerHour => self.kilometres_per_hour(),
+            SpeedUnit::MetresPerSecond => self.metres_per_second(),
+            SpeedUnit::Knots => self.knots(),
+        }
+    }
+}
+
+/// A length, such as an amount of rain or the distance to a lightning strike.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::Length;
+///
+/// let rain = Length::from_inches(0.5);
+///
+/// assert_eq!(rain.millimetres(), 12.7);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Length {
+    metres: f64,
+}
+
+impl Length {
+    /// The number of metres in one inch.
+    const METRES_PER_INCH: f64 = 0.0254;
+
+    /// The number of metres in one foot.
+    const METRES_PER_FOOT: f64 = 0.3048;
+
+    /// The number of metres in one mile.
+    const METRES_PER_MILE: f64 = 1609.344;
+
+    /// Creates a length from inches, the unit Ambient Weather reports rain in.
+    pub fn from_inches(inches: f32) -> Self {
+        Length {
+            metres: f64::from(inches) * Self::METRES_PER_INCH,
+        }
+    }
+
+    /// Creates a length from feet.
+    pub fn from_feet(feet: f32) -> Self {
+        Length {
+            metres: f64::from(feet) * Self::METRES_PER_FOOT,
+        }
+    }
+
+    /// Creates a length from miles.
+    pub fn from_miles(miles: f32) -> Self {
+        Length {
+            metres: f64::from(miles) * Self::METRES_PER_MILE,
+        }
+    }
+
+    /// Creates a length from millimetres.
+    pub fn from_millimetres(millimetres: f32) -> Self {
+        Length {
+            metres: f64::from(millimetres) / 1000.0,
+        }
+    }
+
+    /// Creates a length from centimetres.
+    pub fn from_centimetres(centimetres: f32) -> Self {
+        Length {
+            metres: f64::from(centimetres) / 100.0,
+        }
+    }
+
+    /// Creates a length from metres.
+    pub fn from_metres(metres: f32) -> Self {
+        Length {
+            metres: f64::from(metres),
+        }
+    }
+
+    /// Creates a length from kilometres, the unit Ambient Weather reports lightning distances in.
+    pub fn from_kilometres(kilometres: f32) -> Self {
+        Length {
+            metres: f64::from(kilometres) * 1000.0,
+        }
+    }
+
+    /// Returns the length in inches.
+    pub fn inches(&self) -> f32 {
+        (self.metres / Self::METRES_PER_INCH) as f32
+    }
+
+    /// Returns the length in feet.
+    pub fn feet(&self) -> f32 {
+        (self.metres / Self::METRES_PER_FOOT) as f32
+    }
+
+    /// Returns the length in miles.
+    pub fn miles(&self) -> f32 {
+        (self.metres / Self::METRES_PER_MILE) as f32
+    }
+
+    /// Returns the length in millimetres.
+    pub fn millimetres(&self) -> f32 {
+        (self.metres * 1000.0) as f32
+    }
+
+    /// Returns the length in centimetres.
+    pub fn centimetres(&self) -> f32 {
+        (self.metres * 100.0) as f32
+    }
+
+    /// Returns the length in metres.
+    pub fn metres(&self) -> f32 {
+        self.metres as f32
+    }
+
+    /// Returns the length in kilometres.
+    pub fn kilometres(&self) -> f32 {
+        (self.metres / 1000.0) as f32
+    }
+
+    /// Returns the length in the given unit.
+    pub fn in_unit(&self, unit: LengthUnit) -> f32 {
+        match unit {
+            LengthUnit::Inches => self.inches(),
+            LengthUnit::Feet => self.feet(),
+            LengthUnit::Miles => self.miles(),
+            LengthUnit::Millimetres => self.millimetres(),
+            LengthUnit::Centimetres => self.centimetres(),
+            LengthUnit::Metres => self.metres(),
+            LengthUnit::Kilometres => self.kilometres(),
+        }
+    }
+}
+
+/// The power of sunlight falling on a surface.
+///
+/// Conversions to and from lux assume the spectrum of direct sunlight, where 1 W/m² is about 126.7 lux, so they are only an approximation.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::Irradiance;
+///
+/// let irradiance = Irradiance::from_watts_per_square_metre(100.0);
+///
+/// assert_eq!(irradiance.lux(), 12670.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Irradiance {
+    watts_per_square_metre: f64,
+}
+
+impl Irradiance {
+    /// The approximate number of lux in 1 W/m² of sunlight.
+    const LUX_PER_WATT_PER_SQUARE_METRE: f64 = 126.7;
+
+    /// Creates an irradiance from watts per square metre, the unit Ambient Weather reports in.
+    pub fn from_watts_per_square_metre(watts_per_square_metre: f32) -> Self {
+        Irradiance {
+            watts_per_square_metre: f64::from(watts_per_square_metre),
+        }
+    }
+
+    /// Creates an irradiance from an illuminance in lux, assuming sunlight.
+    pub fn from_lux(lux: f32) -> Self {
+        Irradiance {
+            watts_per_square_metre: f64::from(lux) / Self::LUX_PER_WATT_PER_SQUARE_METRE,
+        }
+    }
+
+    /// Returns the irradiance in watts per square metre.
+    pub fn watts_per_square_metre(&self) -> f32 {
+        self.watts_per_square_metre as f32
+    }
+
+    /// Returns the approximate illuminance in lux, assuming sunlight.
+    pub fn lux(&self) -> f32 {
+        (self.watts_per_square_metre * Self::LUX_PER_WATT_PER_SQUARE_METRE) as f32
+    }
+}
+
+/// The concentration of a pollutant in the air, such as particulate matter or CO₂.
+///
+/// Conversions between a mass concentration and parts per million need the molar mass of the gas, such as [`CO2_MOLAR_MASS`], and assume 25 °C and 1 atm.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{Concentration, CO2_MOLAR_MASS};
+///
+/// let pm25 = Concentration::from_micrograms_per_cubic_metre(12.0);
+/// assert_eq!(pm25.milligrams_per_cubic_metre(), 0.012);
+///
+/// let co2 = Concentration::from_parts_per_million(1000.0, CO2_MOLAR_MASS);
+/// assert_eq!(co2.milligrams_per_cubic_metre().round(), 1800.0);
+/// assert_eq!(co2.parts_per_million(CO2_MOLAR_MASS), 1000.0);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct Concentration {
+    micrograms_per_cubic_metre: f64,
+}
+
+impl Concentration {
+    /// Creates a concentration from micrograms per cubic metre, the unit Ambient Weather reports particulate matter in.
+    pub fn from_micrograms_per_cubic_metre(micrograms_per_cubic_metre: f32) -> Self {
+        Concentration {
+            micrograms_per_cubic_metre: f64::from(micrograms_per_cubic_metre),
+        }
+    }
+
+    /// Creates a concentration from milligrams per cubic metre.
+    pub fn from_milligrams_per_cubic_metre(milligrams_per_cubic_metre: f32) -> Self {
+        Concentration {
+            micrograms_per_cubic_metre: f64::from(milligrams_per_cubic_metre) * 1000.0,
+        }
+    }
+
+    /// Creates a concentration from parts per million of a gas with the given molar mass in g/mol, such as [`CO2_MOLAR_MASS`]. Ambient Weather reports CO₂ in parts per million.
+    pub fn from_parts_per_million(parts_per_million: f32, molar_mass: f32) -> Self {
+        Concentration {
+            micrograms_per_cubic_metre: f64::from(parts_per_million)
+                * f64::from(molar_mass)
+                * 1000.0
+                / MOLAR_VOLUME,
+        }
+    }
+
+    /// Returns the concentration in micrograms per cubic metre.
+    pub fn micrograms_per_cubic_metre(&self) -> f32 {
+        self.micrograms_per_cubic_metre as f32
+    }
+
+    /// Returns the concentration in milligrams per cubic metre.
+    pub fn milligrams_per_cubic_metre(&self) -> f32 {
+        (self.micrograms_per_cubic_metre / 1000.0) as f32
+    }
+
+    /// Returns the concentration in parts per million of a gas with the given molar mass in g/mol, such as [`CO2_MOLAR_MASS`].
+    pub fn parts_per_million(&self, molar_mass: f32) -> f32 {
+        (self.micrograms_per_cubic_metre * MOLAR_VOLUME / (f64::from(molar_mass) * 1000.0)) as f32
+    }
+}
+
+/// A unit a [`Temperature`] can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TemperatureUnit {
+    /// Degrees Fahrenheit (°F).
+    Fahrenheit,
+    /// Degrees Celsius (°C).
+    Celsius,
+    /// Kelvins (K).
+    Kelvin,
+}
+
+impl TemperatureUnit {
+    /// Returns the symbol of the unit, such as `°C`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Fahrenheit => "°F",
+            TemperatureUnit::Celsius => "°C",
+            TemperatureUnit::Kelvin => "K",
+        }
+    }
+
+    /// A private function that returns the suffix added to field names holding values in this unit.
+    pub(crate) fn suffix(&self) -> &'static str {
+        match self {
+            TemperatureUnit::Fahrenheit => "f",
+            TemperatureUnit::Celsius => "c",
+            TemperatureUnit::Kelvin => "k",
+        }
+    }
+}
+
+/// A unit a [`Pressure`] can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PressureUnit {
+    /// Inches of mercury (inHg).
+    InchesOfMercury,
+    /// Hectopascals (hPa), which are the same as millibars.
+    Hectopascals,
+    /// Kilopascals (kPa).
+    Kilopascals,
+    /// Millimetres of mercury (mmHg).
+    MillimetresOfMercury,
+}
+
+impl PressureUnit {
+    /// Returns the symbol of the unit, such as `hPa`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            PressureUnit::InchesOfMercury => "inHg",
+            PressureUnit::Hectopascals => "hPa",
+            PressureUnit::Kilopascals => "kPa",
+            PressureUnit::MillimetresOfMercury => "mmHg",
+        }
+    }
+
+    /// A private function that returns the suffix added to field names holding values in this unit.
+    pub(crate) fn suffix(&self) -> &'static str {
+        match self {
+            PressureUnit::InchesOfMercury => "inhg",
+            PressureUnit::Hectopascals => "hpa",
+            PressureUnit::Kilopascals => "kpa",
+            PressureUnit::MillimetresOfMercury => "mmhg",
+        }
+    }
+}
+
+/// A unit a [`Speed`] can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SpeedUnit {
+    /// Miles per hour (mph).
+    MilesPerHour,
+    /// Kilometres per hour (km/h).
+    KilometresPerHour,
+    /// Metres per second (m/s).
+    MetresPerSecond,
+    /// Knots (kn).
+    Knots,
+}
+
+impl SpeedUnit {
+    /// Returns the symbol of the unit, such as `km/h`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            SpeedUnit::MilesPerHour => "mph",
+            SpeedUnit::KilometresPerHour => "km/h",
+            SpeedUnit::MetresPerSecond => "m/s",
+            SpeedUnit::Knots => "kn",
+        }
+    }
+
+    /// A private function that returns the suffix added to field names holding values in this unit.
+    pub(crate) fn suffix(&self) -> &'static str {
+        match self {
+            SpeedUnit::MilesPerHour => "mph",
+            SpeedUnit::KilometresPerHour => "kmh",
+            SpeedUnit::MetresPerSecond => "ms",
+            SpeedUnit::Knots => "kn",
+        }
+    }
+}
+
+/// A unit a [`Length`] can be expressed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthUnit {
+    /// Inches (in).
+    Inches,
+    /// Feet (ft).
+    Feet,
+    /// Miles (mi).
+    Miles,
+    /// Millimetres (mm).
+    Millimetres,
+    /// Centimetres (cm).
+    Centimetres,
+    /// Metres (m).
+    Metres,
+    /// Kilometres (km).
+    Kilometres,
+}
+
+impl LengthUnit {
+    /// Returns the symbol of the unit, such as `mm`.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            LengthUnit::Inches => "in",
+            LengthUnit::Feet => "ft",
+            LengthUnit::Miles => "mi",
+            LengthUnit::Millimetres => "mm",
+            LengthUnit::Centimetres => "cm",
+            LengthUnit::Metres => "m",
+            LengthUnit::Kilometres => "km",
+        }
+    }
+
+    /// A private function that returns the suffix added to field names holding values in this unit.
+    pub(crate) fn suffix(&self) -> &'static str {
+        self.symbol()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!(
+            (actual - expected).abs() <= expected.abs() * 1e-5,
+            "{actual} is not {expected}"
+        );
+    }
+
+    #[test]
+    fn converts_pressures_by_known_constants() {
+        let standard = Pressure::from_inches_of_mercury(29.92);
+        assert_close(standard.hectopascals(), 1013.207);
+        assert_close(standard.millibars(), 1013.207);
+        assert_close(standard.kilopascals(), 101.3207);
+        assert_close(standard.millimetres_of_mercury(), 759.968);
+
+        assert_close(
+            Pressure::from_hectopascals(1013.25).inches_of_mercury(),
+            29.9213,
+        );
+        assert_close(
+            Pressure::from_millimetres_of_mercury(25.4).inches_of_mercury(),
+            1.0,
+        );
+    }
+
+    #[test]
+    fn converts_speeds_by_known_constants() {
+        let speed = Speed::from_miles_per_hour(10.0);
+        assert_close(speed.metres_per_second(), 4.4704);
+        assert_close(speed.kilometres_per_hour(), 16.09344);
+        assert_close(speed.knots(), 8.689762);
+
+        assert_close(Speed::from_knots(1.0).kilometres_per_hour(), 1.852);
+        assert_close(Speed::from_knots(1.0).metres_per_second(), 0.514444);
+        assert_close(
+            Speed::from_metres_per_second(1.0).miles_per_hour(),
+            2.236936,
+        );
+    }
+
+    #[test]
+    fn converts_lengths_by_known_constants() {
+        assert_close(Length::from_inches(1.0).millimetres(), 25.4);
+        assert_close(Length::from_millimetres(25.4).inches(), 1.0);
+        assert_close(Length::from_feet(1.0).metres(), 0.3048);
+        assert_close(Length::from_miles(1.0).kilometres(), 1.609344);
+        assert_close(Length::from_centimetres(2.54).inches(), 1.0);
+    }
+
+    #[test]
+    fn round_trips_through_every_unit() {
+        for value in [0.5f32, 1.0, 29.92, 1013.25] {
+            let pressure = Pressure::from_inches_of_mercury(value);
+            assert_close(
+                Pressure::from_hectopascals(pressure.hectopascals()).inches_of_mercury(),
+                value,
+            );
+            assert_close(
+                Pressure::from_kilopascals(pressure.kilopascals()).inches_of_mercury(),
+                value,
+            );
+            assert_close(
+                Pressure::from_millimetres_of_mercury(pressure.millimetres_of_mercury())
+                    .inches_of_mercury(),
+                value,
+            );
+
+            let speed = Speed::from_miles_per_hour(value);
+            assert_close(
+                Speed::from_metres_per_second(speed.metres_per_second()).miles_per_hour(),
+                value,
+            );
+            assert_close(Speed::from_knots(speed.knots()).miles_per_hour(), value);
+            assert_close(
+                Speed::from_knots(Speed::from_metres_per_second(speed.metres_per_second()).knots())
+                    .miles_per_hour(),
+                value,
+            );
+
+            let length = Length::from_inches(value);
+            assert_close(
+                Length::from_millimetres(length.millimetres()).inches(),
+                value,
+            );
+            assert_close(Length::from_metres(length.metres()).inches(), value);
+        }
+    }
+
+    #[test]
+    fn converts_temperatures_by_known_constants() {
+        assert_close(Temperature::from_celsius(0.0).fahrenheit(), 32.0);
+        assert_close(Temperature::from_celsius(0.0).kelvin(), 273.15);
+        assert_close(Temperature::from_kelvin(373.15).fahrenheit(), 212.0);
+    }
+}
@@ -1,22 +1,60 @@
-use serde::{Serialize,Deserialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
 
-#[derive(Serialize, Deserialize, Default)]
+use crate::{
+    field_kind::FieldKind, json_number, AmbientWeatherError, Concentration, Irradiance, Length,
+    LocalDateTime, Pressure, SensorChannels, Speed, Temperature, TimeZone, Timestamp,
+    WindDirection, CO2_MOLAR_MASS,
+};
+
+/// A private macro that writes accessor methods returning a field of [`WeatherData`] as a unit-carrying type.
+macro_rules! unit_accessors {
+    ($($(#[$doc:meta])* $name:ident => $field:ident, $unit:ident::$constructor:ident;)*) => {
+        $(
+            $(#[$doc])*
+            pub fn $name(&self) -> Option<$unit> {
+                self.$field.map(|value| $unit::$constructor(value.into()))
+            }
+        )*
+    };
+}
+
+/// A single weather data record, as reported by an Ambient Weather device.
+///
+/// Each field matches one of the parameters in Ambient Weather's [list of device parameters](https://github.com/ambient-weather/api-docs/wiki/Device-Data-Specs), and is `None` when the device did not report it. The numbered sensors, such as `temp1f` through `temp10f`, are collected in [`WeatherData::channels`].
+#[derive(Serialize, Deserialize, Default, Debug, Clone)]
 pub struct WeatherData {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub winddir: Option<u16>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub windspeedmph: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub windgustmph: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub maxdailygust: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub windgustdir: Option<u16>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub windspdmph_avg2m: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub winddir_avg2m: Option<u16>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub windspdmph_avg10m: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub winddir_avg10m: Option<u16>,
@@ -24,66 +62,613 @@ pub struct WeatherData {
     pub humidity: Option<u8>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub humidityin: Option<u8>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub tempf: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub tempinf: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub hourlyrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub dailyrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        rename = "24hourrainin",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub hour24rainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub weeklyrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub monthlyrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub yearlyrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub eventrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub totalrainin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub baromrelin: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub baromabsin: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub uv: Option<u8>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub solarradiation: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub co2: Option<u16>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pm25: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pm25_24h: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pm25_in: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub pm25_in_24h: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lightning_day: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub lightning_hour: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub lightning_time: Option<u64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lightning_time: Option<Timestamp>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
     pub lightning_distance: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tz: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub dateutc: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub lastRain: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub dewPoint: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub feelsLike: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub date: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub feelsLikein: Option<f32>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub dewPointin: Option<f32>,
+    pub tz: Option<TimeZone>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dateutc: Option<Timestamp>,
+    #[serde(
+        default,
+        with = "crate::timestamp::iso8601",
+        rename = "lastRain",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub last_rain: Option<Timestamp>,
+    #[serde(
+        rename = "dewPoint",
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub dew_point: Option<f32>,
+    #[serde(
+        rename = "feelsLike",
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub feels_like: Option<f32>,
+    #[serde(
+        default,
+        with = "crate::timestamp::iso8601",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub date: Option<Timestamp>,
+    #[serde(
+        rename = "feelsLikein",
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub feels_like_in: Option<f32>,
+    #[serde(
+        rename = "dewPointin",
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub dew_point_in: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battout: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battin: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batt_25: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batt_25in: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batt_lightning: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batt_co2: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub batt_cellgateway: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battrain: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm25: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm25_24h: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm25_in: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm25_in_24h: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub co2_in: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub co2_in_24h: Option<u16>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm10_in: Option<f32>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm10_in_24h: Option<f32>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm_in_temp: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pm_in_humidity: Option<u8>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm25_in_aqin: Option<f32>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm25_in_24h_aqin: Option<f32>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm10_in_aqin: Option<f32>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm10_in_24h_aqin: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub co2_in_aqin: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub co2_in_24h_aqin: Option<u16>,
+    #[serde(
+        serialize_with = "json_number::serialize_option",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub pm_in_temp_aqin: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pm_in_humidity_aqin: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm25_aqin: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm25_24h_aqin: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm10_aqin: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub aqi_pm10_24h_aqin: Option<u16>,
+    #[serde(flatten)]
+    pub channels: SensorChannels,
+    /// Every field the device reported that this crate does not know about yet, kept as raw JSON so that it can still be used and is written back out on serialization.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
+}
+
+impl WeatherData {
+    /// Returns a field this crate does not model yet, decoded as `T`. Returns `None` if the device did not report the field or it cannot be decoded as `T`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::WeatherData;
+    ///
+    /// let data: WeatherData = serde_json::from_str(r#"{ "tempf": 66.9, "newsensor1": 12.5 }"#).unwrap();
+    ///
+    /// assert_eq!(data.extra_field::<f32>("newsensor1"), Some(12.5));
+    /// assert_eq!(data.unknown_fields().collect::<Vec<_>>(), ["newsensor1"]);
+    /// ```
+    pub fn extra_field<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        self.extra
+            .get(name)
+            .and_then(|value| T::deserialize(value).ok())
+    }
+
+    /// Returns the names of every field the device reported that this crate does not model yet.
+    pub fn unknown_fields(&self) -> impl Iterator<Item = &str> {
+        self.extra.keys().map(String::as_str)
+    }
+
+    /// Returns the time of the observation, `dateutc`, in the device's time zone, `tz`. Returns `None` if either is missing or the rules of the time zone are unknown.
+    pub fn local_time(&self) -> Option<LocalDateTime> {
+        self.dateutc?.in_time_zone(self.tz.as_ref()?)
+    }
+
+    unit_accessors! {
+        /// Returns the outdoor temperature, from `tempf`.
+        ///
+        /// # Examples
+        ///
+        /// ```
+        /// use ambient_weather_api::WeatherData;
+        ///
+        /// let data: WeatherData = serde_json::from_str(r#"{ "tempf": 50.0, "windspeedmph": 10.0 }"#).unwrap();
+        ///
+        /// assert_eq!(data.temperature().map(|temperature| temperature.celsius()), Some(10.0));
+        /// assert_eq!(data.wind_speed().map(|speed| speed.kilometres_per_hour().round()), Some(16.0));
+        /// assert_eq!(data.indoor_temperature(), None);
+        /// ```
+        temperature => tempf, Temperature::from_fahrenheit;
+        /// Returns the indoor temperature, from `tempinf`.
+        indoor_temperature => tempinf, Temperature::from_fahrenheit;
+        /// Returns the outdoor "feels like" temperature, from `feelsLike`.
+        feels_like => feels_like, Temperature::from_fahrenheit;
+        /// Returns the indoor "feels like" temperature, from `feelsLikein`.
+        indoor_feels_like => feels_like_in, Temperature::from_fahrenheit;
+        /// Returns the outdoor dew point, from `dewPoint`.
+        dew_point => dew_point, Temperature::from_fahrenheit;
+        /// Returns the indoor dew point, from `dewPointin`.
+        indoor_dew_point => dew_point_in, Temperature::from_fahrenheit;
+        /// Returns the temperature measured by the indoor particulate matter sensor, from `pm_in_temp`.
+        pm_in_temperature => pm_in_temp, Temperature::from_fahrenheit;
+        /// Returns the temperature measured by the AQIN sensor, from `pm_in_temp_aqin`.
+        pm_in_temperature_aqin => pm_in_temp_aqin, Temperature::from_fahrenheit;
+
+        /// Returns the relative (sea level) pressure, from `baromrelin`.
+        relative_pressure => baromrelin, Pressure::from_inches_of_mercury;
+        /// Returns the absolute (station) pressure, from `baromabsin`.
+        absolute_pressure => baromabsin, Pressure::from_inches_of_mercury;
+
+        /// Returns the instantaneous wind speed, from `windspeedmph`.
+        wind_speed => windspeedmph, Speed::from_miles_per_hour;
+        /// Returns the largest wind gust of the last 10 minutes, from `windgustmph`.
+        wind_gust => windgustmph, Speed::from_miles_per_hour;
+        /// Returns the largest wind gust of the day, from `maxdailygust`.
+        max_daily_gust => maxdailygust, Speed::from_miles_per_hour;
+        /// Returns the average wind speed over 2 minutes, from `windspdmph_avg2m`.
+        wind_speed_avg_2m => windspdmph_avg2m, Speed::from_miles_per_hour;
+        /// Returns the average wind speed over 10 minutes, from `windspdmph_avg10m`.
+        wind_speed_avg_10m => windspdmph_avg10m, Speed::from_miles_per_hour;
+
+        /// Returns the direction the wind is blowing from, from `winddir`.
+        wind_direction => winddir, WindDirection::from_degrees;
+        /// Returns the direction of the largest wind gust of the last 10 minutes, from `windgustdir`.
+        wind_gust_direction => windgustdir, WindDirection::from_degrees;
+        /// Returns the average wind direction over 2 minutes, from `winddir_avg2m`.
+        wind_direction_avg_2m => winddir_avg2m, WindDirection::from_degrees;
+        /// Returns the average wind direction over 10 minutes, from `winddir_avg10m`.
+        wind_direction_avg_10m => winddir_avg10m, WindDirection::from_degrees;
+
+        /// Returns the rain of the current hour, from `hourlyrainin`.
+        hourly_rain => hourlyrainin, Length::from_inches;
+        /// Returns the rain of the current day, from `dailyrainin`.
+        daily_rain => dailyrainin, Length::from_inches;
+        /// Returns the rain of the last 24 hours, from `24hourrainin`.
+        rain_24h => hour24rainin, Length::from_inches;
+        /// Returns the rain of the current week, from `weeklyrainin`.
+        weekly_rain => weeklyrainin, Length::from_inches;
+        /// Returns the rain of the current month, from `monthlyrainin`.
+        monthly_rain => monthlyrainin, Length::from_inches;
+        /// Returns the rain of the current year, from `yearlyrainin`.
+        yearly_rain => yearlyrainin, Length::from_inches;
+        /// Returns the rain of the current rain event, from `eventrainin`.
+        event_rain => eventrainin, Length::from_inches;
+        /// Returns the rain since the rain gauge was last reset, from `totalrainin`.
+        total_rain => totalrainin, Length::from_inches;
+        /// Returns the distance to the last lightning strike, from `lightning_distance`.
+        lightning_distance => lightning_distance, Length::from_kilometres;
+
+        /// Returns the solar radiation, from `solarradiation`.
+        solar_radiation => solarradiation, Irradiance::from_watts_per_square_metre;
+
+        /// Returns the outdoor PM2.5 concentration, from `pm25`.
+        pm25 => pm25, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the 24 hour average outdoor PM2.5 concentration, from `pm25_24h`.
+        pm25_24h => pm25_24h, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the indoor PM2.5 concentration, from `pm25_in`.
+        pm25_in => pm25_in, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the 24 hour average indoor PM2.5 concentration, from `pm25_in_24h`.
+        pm25_in_24h => pm25_in_24h, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the indoor PM10 concentration, from `pm10_in`.
+        pm10_in => pm10_in, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the 24 hour average indoor PM10 concentration, from `pm10_in_24h`.
+        pm10_in_24h => pm10_in_24h, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the PM2.5 concentration measured by the AQIN sensor, from `pm25_in_aqin`.
+        pm25_in_aqin => pm25_in_aqin, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the 24 hour average PM2.5 concentration measured by the AQIN sensor, from `pm25_in_24h_aqin`.
+        pm25_in_24h_aqin => pm25_in_24h_aqin, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the PM10 concentration measured by the AQIN sensor, from `pm10_in_aqin`.
+        pm10_in_aqin => pm10_in_aqin, Concentration::from_micrograms_per_cubic_metre;
+        /// Returns the 24 hour average PM10 concentration measured by the AQIN sensor, from `pm10_in_24h_aqin`.
+        pm10_in_24h_aqin => pm10_in_24h_aqin, Concentration::from_micrograms_per_cubic_metre;
+    }
+
+    /// Returns the outdoor CO₂ concentration, from `co2`.
+    pub fn co2(&self) -> Option<Concentration> {
+        co2_concentration(self.co2)
+    }
+
+    /// Returns the indoor CO₂ concentration, from `co2_in`.
+    pub fn co2_in(&self) -> Option<Concentration> {
+        co2_concentration(self.co2_in)
+    }
+
+    /// Returns the 24 hour average indoor CO₂ concentration, from `co2_in_24h`.
+    pub fn co2_in_24h(&self) -> Option<Concentration> {
+        co2_concentration(self.co2_in_24h)
+    }
+
+    /// Returns the CO₂ concentration measured by the AQIN sensor, from `co2_in_aqin`.
+    pub fn co2_in_aqin(&self) -> Option<Concentration> {
+        co2_concentration(self.co2_in_aqin)
+    }
+
+    /// Returns the 24 hour average CO₂ concentration measured by the AQIN sensor, from `co2_in_24h_aqin`.
+    pub fn co2_in_24h_aqin(&self) -> Option<Concentration> {
+        co2_concentration(self.co2_in_24h_aqin)
+    }
+}
+
+/// A private function that turns a CO₂ reading in parts per million into a [`Concentration`].
+fn co2_concentration(parts_per_million: Option<u16>) -> Option<Concentration> {
+    parts_per_million.map(|parts_per_million| {
+        Concentration::from_parts_per_million(parts_per_million.into(), CO2_MOLAR_MASS)
+    })
+}
+
+/// How strictly [`WeatherData`] records are decoded from the API's JSON.
+///
+/// Devices and firmware versions do not all report their fields the same way. Some send `45.0` or `"45"` where a whole number is expected, and some send `-9999` or `"N/A"` for a sensor that has no reading.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{DecodeMode, WeatherData};
+///
+/// let record = serde_json::json!({ "humidity": 45.0, "uv": "3", "tempf": -9999 });
+///
+/// let data = WeatherData::decode(&record, DecodeMode::Lenient).unwrap();
+/// assert_eq!(data.humidity, Some(45));
+/// assert_eq!(data.uv, Some(3));
+/// assert_eq!(data.tempf, None);
+///
+/// assert!(WeatherData::decode(&record, DecodeMode::Strict).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum DecodeMode {
+    /// Repairs what it can. Floats are rounded for whole number fields, numeric strings are parsed, and sentinel values such as `-9999`, `"N/A"` or out-of-range numbers become `None`. This is the default.
+    #[default]
+    Lenient,
+    /// Fails on any value that does not exactly match the type of its field.
+    Strict,
+}
+
+/// The numbers devices report in place of a missing reading.
+const SENTINEL_NUMBERS: [f64; 2] = [-9999.0, -999.0];
+
+impl WeatherData {
+    /// Decodes a record from the JSON the API returns for it, as strictly as `mode` asks.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`AmbientWeatherError::Decode`], naming the offending field where possible, if the record cannot be decoded. In lenient mode this only happens when the record is not a JSON object, or when its fields still conflict with each other once every field that does not fit has been repaired or cleared.
+    pub fn decode(value: &Value, mode: DecodeMode) -> Result<WeatherData, AmbientWeatherError> {
+        decode_weather_data(value, mode)
+    }
+}
+
+/// A private function for decoding a single weather data record. When decoding fails, each field is retried on its own so the error can name the field that caused it.
+pub(crate) fn decode_weather_data(
+    value: &Value,
+    mode: DecodeMode,
+) -> Result<WeatherData, AmbientWeatherError> {
+    match (mode, value) {
+        (DecodeMode::Lenient, Value::Object(record)) => decode_leniently(record.clone()),
+        _ => decode_strictly(value),
+    }
+}
+
+/// A private function that decodes a record exactly as it is, naming the field that fails.
+fn decode_strictly(value: &Value) -> Result<WeatherData, AmbientWeatherError> {
+    WeatherData::deserialize(value).map_err(|err| {
+        // Report the first field that fails on its own, along with its own error, so that the two always match.
+        let failing_field = value.as_object().and_then(|record| {
+            record.iter().find_map(|(key, field_value)| {
+                decode_alone(key, field_value)
+                    .err()
+                    .map(|err| (key.clone(), err))
+            })
+        });
+
+        match failing_field {
+            Some((field, err)) => AmbientWeatherError::decode(Some(field), err),
+            None => AmbientWeatherError::decode(None, err),
+        }
+    })
+}
+
+/// A private function that decodes a record, repairing or clearing every field that does not fit its type and clearing sentinel values. Each field is checked against its own type, so the record is only decoded once unless a date or time zone field is invalid.
+fn decode_leniently(mut record: Map<String, Value>) -> Result<WeatherData, AmbientWeatherError> {
+    let mut others = Vec::new();
+
+    // Unknown fields are kept exactly as they are, so only known fields are repaired and checked for sentinels.
+    for (key, value) in record.iter_mut() {
+        let Some(kind) = FieldKind::of::<WeatherData, SensorChannels>(key) else {
+            continue;
+        };
+
+        *value = kind.repair(value);
+        if is_sentinel(value) {
+            *value = Value::Null;
+        } else if kind == FieldKind::Other && !value.is_null() {
+            others.push(key.clone());
+        }
+    }
+
+    if let Ok(data) = WeatherData::deserialize(&Value::Object(record.clone())) {
+        return Ok(data);
+    }
+
+    // Only fields of other types, such as dates, can still fail, so those that fail on their own are cleared.
+    for key in others {
+        if let Some(value) = record.get_mut(&key) {
+            if decode_alone(&key, value).is_err() {
+                *value = Value::Null;
+            }
+        }
+    }
+
+    // Every field decodes on its own by now, so this only fails if fields conflict with each other, which is reported rather than papered over.
+    WeatherData::deserialize(&Value::Object(record))
+        .map_err(|err| AmbientWeatherError::decode(None, err))
+}
+
+/// A private function that decodes a record made of a single field.
+fn decode_alone(key: &str, value: &Value) -> Result<WeatherData, serde_json::Error> {
+    let single_field = Map::from_iter([(key.to_string(), value.clone())]);
+
+    WeatherData::deserialize(&Value::Object(single_field))
+}
+
+/// A private function that checks whether a value is one devices report in place of a missing reading.
+fn is_sentinel(value: &Value) -> bool {
+    match value {
+        Value::Number(number) => number
+            .as_f64()
+            .is_some_and(|number| SENTINEL_NUMBERS.contains(&number)),
+        Value::String(text) => matches!(
+            text.trim().to_ascii_lowercase().as_str(),
+            "" | "n/a" | "na" | "nan" | "null" | "none" | "--" | "-"
+        ),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lenient(record: Value) -> WeatherData {
+        WeatherData::decode(&record, DecodeMode::Lenient).unwrap()
+    }
+
+    #[test]
+    fn clears_sentinel_values() {
+        let data = lenient(serde_json::json!({
+            "tempf": -9999,
+            "tempinf": "-999",
+            "humidity": "N/A",
+            "uv": "--",
+            "temp2f": -9999.0,
+            "dateutc": -9999,
+            "baromrelin": 29.92,
+        }));
+
+        assert_eq!(data.tempf, None);
+        assert_eq!(data.tempinf, None);
+        assert_eq!(data.humidity, None);
+        assert_eq!(data.uv, None);
+        assert_eq!(data.channels.temperature.get(2), None);
+        assert_eq!(data.dateutc, None);
+        assert_eq!(data.baromrelin, Some(29.92));
+    }
+
+    #[test]
+    fn repairs_or_clears_invalid_values() {
+        let data = lenient(serde_json::json!({
+            "humidity": 45.6,
+            "humidityin": 300,
+            "winddir": "270",
+            "windgustdir": -20,
+            "tempf": "71.5",
+            "batt1": "1",
+            "soilhum2": 101.2,
+            "dateutc": "yesterday",
+            "tz": 5,
+        }));
+
+        assert_eq!(data.humidity, Some(46));
+        assert_eq!(data.humidityin, None);
+        assert_eq!(data.winddir, Some(270));
+        assert_eq!(data.windgustdir, None);
+        assert_eq!(data.tempf, Some(71.5));
+        assert_eq!(data.channels.battery.get(1), Some(1));
+        assert_eq!(data.channels.soil_humidity.get(2), Some(101));
+        assert_eq!(data.dateutc, None);
+        assert!(data.tz.is_none());
+    }
+
+    #[test]
+    fn keeps_unknown_fields_as_they_are() {
+        let data = lenient(serde_json::json!({ "someNewField": -9999, "otherField": "N/A" }));
+
+        assert_eq!(data.extra["someNewField"], -9999);
+        assert_eq!(data.extra["otherField"], "N/A");
+    }
+
+    #[test]
+    fn fails_on_records_that_are_not_objects() {
+        for record in [
+            serde_json::json!([1, 2]),
+            serde_json::json!("tempf"),
+            Value::Null,
+        ] {
+            assert!(matches!(
+                WeatherData::decode(&record, DecodeMode::Lenient),
+                Err(AmbientWeatherError::Decode { field: None, .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn names_the_failing_field_when_strict() {
+        let record = serde_json::json!({ "tempf": 70.1, "humidity": 45.5 });
+
+        match WeatherData::decode(&record, DecodeMode::Strict) {
+            Err(AmbientWeatherError::Decode { field, .. }) => {
+                assert_eq!(field.as_deref(), Some("humidity"))
+            }
+            other => panic!("expected a decode error, got {other:?}"),
+        }
+    }
 }
@@ -0,0 +1,300 @@
+use std::fmt;
+
+use crate::{Speed, WeatherData};
+
+/// The names of the 16 points of the compass, clockwise from north.
+const COMPASS_POINTS: [&str; 16] = [
+    "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW", "NW",
+    "NNW",
+];
+
+/// Resultant vectors shorter than this, relative to the number of directions averaged, have no meaningful direction.
+const CALM: f64 = 1e-9;
+
+/// The direction the wind is blowing from, clockwise from north, as Ambient Weather reports it in `winddir`.
+///
+/// Directions wrap around at north, so they should not be averaged as plain numbers: the mean of 350° and 10° is north, not south. Use [`WindDirection::mean`] instead.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::WindDirection;
+///
+/// let direction = WindDirection::from_degrees(212.0);
+///
+/// assert_eq!(direction.compass_point(), "SSW");
+/// assert_eq!(WindDirection::from_degrees(360.0).degrees(), 0.0);
+/// assert_eq!(WindDirection::from_degrees(-90.0).to_string(), "W");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Default)]
+pub struct WindDirection {
+    degrees: f64,
+}
+
+impl WindDirection {
+    /// Creates a direction from degrees clockwise from north. Degrees outside of 0 to 360 are wrapped around.
+    pub fn from_degrees(degrees: f32) -> Self {
+        WindDirection::from_f64_degrees(f64::from(degrees))
+    }
+
+    /// A private function that creates a direction from degrees at full precision, wrapping them into 0 to 360.
+    fn from_f64_degrees(degrees: f64) -> Self {
+        let degrees = degrees.rem_euclid(360.0);
+
+        // `rem_euclid` can round up to 360 itself for tiny negative angles.
+        WindDirection {
+            degrees: if degrees >= 360.0 { 0.0 } else { degrees },
+        }
+    }
+
+    /// Returns the direction in degrees clockwise from north, from 0 up to but not including 360.
+    pub fn degrees(&self) -> f32 {
+        let degrees = self.degrees as f32;
+
+        if degrees >= 360.0 {
+            0.0
+        } else {
+            degrees
+        }
+    }
+
+    /// Returns the direction in radians clockwise from north.
+    pub fn radians(&self) -> f32 {
+        self.degrees.to_radians() as f32
+    }
+
+    /// Returns the nearest of the 16 points of the compass, such as `N`, `NNE` or `SW`.
+    pub fn compass_point(&self) -> &'static str {
+        COMPASS_POINTS[(self.degrees / 22.5).round() as usize % COMPASS_POINTS.len()]
+    }
+
+    /// Returns the circular mean of a sequence of directions, by averaging them as unit vectors.
+    ///
+    /// Returns `None` if there are no directions, or if they cancel each other out, such as north and south.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::WindDirection;
+    ///
+    /// let directions = [350.0, 10.0].map(WindDirection::from_degrees);
+    ///
+    /// assert_eq!(WindDirection::mean(directions).unwrap().compass_point(), "N");
+    /// assert_eq!(WindDirection::mean([0.0, 180.0].map(WindDirection::from_degrees)), None);
+    /// ```
+    pub fn mean(directions: impl IntoIterator<Item = WindDirection>) -> Option<WindDirection> {
+        let (east, north, count) =
+            directions
+                .into_iter()
+                .fold((0.0, 0.0, 0usize), |(east, north, count), direction| {
+                    let radians = direction.degrees.to_radians();
+                    (east + radians.sin(), north + radians.cos(), count + 1)
+                });
+
+        if count == 0 || east.hypot(north) < CALM * count as f64 {
+            return None;
+        }
+
+        Some(WindDirection::from_f64_degrees(
+            east.atan2(north).to_degrees(),
+        ))
+    }
+
+    /// Returns the circular mean of the `winddir` of a sequence of records, such as those returned by [`AmbientWeatherClient::historic`](crate::AmbientWeatherClient::historic), the same way as [`WindDirection::mean`].
+    ///
+    /// Every record counts equally, however hard the wind was blowing. Use [`WindVector::mean_of`] to weigh each direction by its wind speed.
+    pub fn mean_of(records: &[WeatherData]) -> Option<WindDirection> {
+        WindDirection::mean(records.iter().filter_map(WeatherData::wind_direction))
+    }
+}
+
+impl fmt::Display for WindDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.compass_point())
+    }
+}
+
+/// A wind as a vector, split into its eastward (u) and northward (v) components, as is usual in meteorology.
+///
+/// The components point the way the wind is blowing to, so a wind from the west has a positive u component.
+///
+/// # Examples
+///
+/// ```
+/// use ambient_weather_api::{Speed, WindDirection, WindVector};
+///
+/// let wind = WindVector::new(Speed::from_metres_per_second(10.0), WindDirection::from_degrees(270.0));
+///
+/// assert_eq!(wind.u().metres_per_second().round(), 10.0);
+/// assert!(wind.v().metres_per_second().abs() < 1e-3);
+/// assert_eq!(wind.direction().unwrap().compass_point(), "W");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct WindVector {
+    u: f64,
+    v: f64,
+}
+
+impl WindVector {
+    /// Creates a vector from a wind speed and the direction the wind is blowing from.
+    pub fn new(speed: Speed, direction: WindDirection) -> Self {
+        let speed = f64::from(speed.metres_per_second());
+        let radians = direction.degrees.to_radians();
+
+        WindVector {
+            u: -speed * radians.sin(),
+            v: -speed * radians.cos(),
+        }
+    }
+
+    /// Creates a vector from its eastward (u) and northward (v) components.
+    pub fn from_components(u: Speed, v: Speed) -> Self {
+        WindVector {
+            u: f64::from(u.metres_per_second()),
+            v: f64::from(v.metres_per_second()),
+        }
+    }
+
+    /// Returns the eastward (u) component. It is negative when the wind blows toward the west.
+    pub fn u(&self) -> Speed {
+        Speed::from_metres_per_second(self.u as f32)
+    }
+
+    /// Returns the northward (v) component. It is negative when the wind blows toward the south.
+    pub fn v(&self) -> Speed {
+        Speed::from_metres_per_second(self.v as f32)
+    }
+
+    /// Returns the wind speed, the length of the vector.
+    pub fn speed(&self) -> Speed {
+        Speed::from_metres_per_second(self.u.hypot(self.v) as f32)
+    }
+
+    /// Returns the direction the wind is blowing from, or `None` if the air is calm.
+    pub fn direction(&self) -> Option<WindDirection> {
+        (self.u.hypot(self.v) >= CALM)
+            .then(|| WindDirection::from_f64_degrees((-self.u).atan2(-self.v).to_degrees()))
+    }
+
+    /// Returns the mean of a sequence of vectors, or `None` if there are none.
+    ///
+    /// The speed of the mean is the net movement of the air, which is lower than the mean wind speed when the direction varies.
+    pub fn mean(vectors: impl IntoIterator<Item = WindVector>) -> Option<WindVector> {
+        let (u, v, count) = vectors
+            .into_iter()
+            .fold((0.0, 0.0, 0usize), |(u, v, count), vector| {
+                (u + vector.u, v + vector.v, count + 1)
+            });
+
+        (count > 0).then(|| WindVector {
+            u: u / count as f64,
+            v: v / count as f64,
+        })
+    }
+
+    /// Returns the mean wind vector of a sequence of records, from their `windspeedmph` and `winddir`, the same way as [`WindVector::mean`]. Stronger winds weigh more heavily on the direction of the mean than light ones.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use ambient_weather_api::{WeatherData, WindVector};
+    ///
+    /// let records: Vec<WeatherData> = [(20.0, 350), (20.0, 10), (1.0, 180)]
+    ///     .into_iter()
+    ///     .map(|(speed, direction)| serde_json::from_value(serde_json::json!({
+    ///         "windspeedmph": speed,
+    ///         "winddir": direction,
+    ///     })).unwrap())
+    ///     .collect();
+    ///
+    /// let mean = WindVector::mean_of(&records).unwrap();
+    /// assert_eq!(mean.direction().unwrap().compass_point(), "N");
+    /// ```
+    pub fn mean_of(records: &[WeatherData]) -> Option<WindVector> {
+        WindVector::mean(records.iter().filter_map(WeatherData::wind_vector))
+    }
+}
+
+impl WeatherData {
+    /// Returns the instantaneous wind as a vector, from `windspeedmph` and `winddir`.
+    pub fn wind_vector(&self) -> Option<WindVector> {
+        Some(WindVector::new(self.wind_speed()?, self.wind_direction()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn directions(degrees: &[f32]) -> Vec<WindDirection> {
+        degrees
+            .iter()
+            .copied()
+            .map(WindDirection::from_degrees)
+            .collect()
+    }
+
+    #[test]
+    fn averages_directions_across_north() {
+        let mean = WindDirection::mean(directions(&[350.0, 10.0])).unwrap();
+        assert!(mean.degrees() < 1e-3 || mean.degrees() > 360.0 - 1e-3);
+
+        let mean = WindDirection::mean(directions(&[340.0, 0.0, 20.0])).unwrap();
+        assert!(mean.degrees() < 1e-3 || mean.degrees() > 360.0 - 1e-3);
+
+        let mean = WindDirection::mean(directions(&[355.0, 335.0])).unwrap();
+        assert!((mean.degrees() - 345.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn has_no_mean_without_directions_or_when_they_cancel() {
+        assert_eq!(WindDirection::mean(Vec::new()), None);
+        assert_eq!(WindDirection::mean(directions(&[90.0, 270.0])), None);
+        assert_eq!(WindDirection::mean(directions(&[0.0, 120.0, 240.0])), None);
+    }
+
+    #[test]
+    fn has_no_direction_when_calm() {
+        let calm = WindVector::new(Speed::default(), WindDirection::from_degrees(90.0));
+
+        assert_eq!(calm.speed(), Speed::default());
+        assert_eq!(calm.direction(), None);
+        assert_eq!(WindVector::default().direction(), None);
+    }
+
+    #[test]
+    fn cancels_opposite_winds_to_a_calm_mean() {
+        let speed = Speed::from_miles_per_hour(10.0);
+        let mean = WindVector::mean([
+            WindVector::new(speed, WindDirection::from_degrees(0.0)),
+            WindVector::new(speed, WindDirection::from_degrees(180.0)),
+        ])
+        .unwrap();
+
+        assert!(mean.speed().metres_per_second() < 1e-6);
+        assert_eq!(mean.direction(), None);
+        assert_eq!(WindVector::mean(Vec::new()), None);
+    }
+
+    #[test]
+    fn skips_records_without_wind() {
+        let records: Vec<WeatherData> = [
+            serde_json::json!({ "windspeedmph": 0.0, "winddir": 90 }),
+            serde_json::json!({ "windspeedmph": 10.0 }),
+            serde_json::json!({ "winddir": 180 }),
+        ]
+        .into_iter()
+        .map(|record| serde_json::from_value(record).unwrap())
+        .collect();
+
+        let mean = WindVector::mean_of(&records).unwrap();
+        assert_eq!(mean.speed(), Speed::default());
+        assert_eq!(mean.direction(), None);
+
+        // The calm record still has a direction, which counts as much as any other
+        assert_eq!(
+            WindDirection::mean_of(&records).map(|mean| mean.compass_point()),
+            Some("SE")
+        );
+    }
+}
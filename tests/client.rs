@@ -0,0 +1,638 @@
+//! Tests for the async client against a local stand-in for the Ambient Weather REST API.
+
+use ambient_weather_api::*;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+/// A response of the stand-in server.
+struct Reply {
+    status: u16,
+    headers: Vec<(&'static str, String)>,
+    body: String,
+}
+
+impl Reply {
+    fn json(body: impl Into<String>) -> Reply {
+        Reply {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    fn status(status: u16) -> Reply {
+        Reply {
+            status,
+            headers: Vec::new(),
+            body: String::from(r#"{"error":"stand-in"}"#),
+        }
+    }
+
+    fn header(mut self, name: &'static str, value: impl Into<String>) -> Reply {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+/// A running stand-in server, which answers each request with whatever its route returns for the request target and the number of requests before it.
+struct StandIn {
+    base_url: String,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl StandIn {
+    async fn start(route: impl Fn(&str, usize) -> Reply + Send + Sync + 'static) -> StandIn {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base_url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let route = Arc::new(route);
+        let served = Arc::new(AtomicUsize::new(0));
+
+        let log = Arc::clone(&requests);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                let (log, route, served) =
+                    (Arc::clone(&log), Arc::clone(&route), Arc::clone(&served));
+                tokio::spawn(async move {
+                    let mut socket = socket;
+                    let Some(target) = read_target(&mut socket).await else {
+                        return;
+                    };
+                    log.lock().unwrap().push(target.clone());
+                    let reply = route(&target, served.fetch_add(1, Ordering::SeqCst));
+                    respond(&mut socket, reply).await;
+                });
+            }
+        });
+
+        StandIn { base_url, requests }
+    }
+
+    /// Returns the path of every request so far, without its query.
+    fn paths(&self) -> Vec<String> {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|target| target.split('?').next().unwrap().to_string())
+            .collect()
+    }
+
+    fn builder(&self, device: DeviceSelector) -> AmbientWeatherClientBuilder {
+        AmbientWeatherClient::builder(AmbientWeatherAPICredentials {
+            api_key: String::from("api-key"),
+            app_key: String::from("app-key"),
+            device,
+            use_new_api_endpoint: false,
+        })
+        .base_url(&self.base_url)
+        .rate_limits(RateLimits {
+            per_api_key: 0.0,
+            per_application_key: 0.0,
+        })
+    }
+
+    fn client(&self, device: DeviceSelector, retry_policy: RetryPolicy) -> AmbientWeatherClient {
+        self.builder(device)
+            .retry_policy(retry_policy)
+            .build()
+            .unwrap()
+    }
+}
+
+async fn read_target(socket: &mut TcpStream) -> Option<String> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    while !buffer.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let head = String::from_utf8_lossy(&buffer).to_string();
+    Some(head.lines().next()?.split(' ').nth(1)?.to_string())
+}
+
+async fn respond(socket: &mut TcpStream, reply: Reply) {
+    let headers: String = reply
+        .headers
+        .iter()
+        .map(|(name, value)| format!("{name}: {value}\r\n"))
+        .collect();
+    let response = format!(
+        "HTTP/1.1 {} Stand-In\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n{headers}\r\n{}",
+        reply.status,
+        reply.body.len(),
+        reply.body
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+/// Retries quickly, so that tests do not wait on real backoffs.
+fn quick_retries(max_attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+        max_attempts,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        jitter: 0.0,
+        ..RetryPolicy::default()
+    }
+}
+
+#[tokio::test]
+async fn retries_server_errors_until_success() {
+    let server = StandIn::start(|_, served| match served {
+        0 | 1 => Reply::status(503),
+        _ => Reply::json("[]"),
+    })
+    .await;
+
+    let devices = server
+        .client(DeviceSelector::Index(0), quick_retries(3))
+        .list_devices()
+        .await
+        .unwrap();
+
+    assert!(devices.is_empty());
+    assert_eq!(server.paths().len(), 3);
+}
+
+#[tokio::test]
+async fn gives_up_after_the_last_attempt() {
+    let server = StandIn::start(|_, _| Reply::status(500)).await;
+
+    let err = server
+        .client(DeviceSelector::Index(0), quick_retries(3))
+        .list_devices()
+        .await
+        .unwrap_err();
+
+    match &err {
+        AmbientWeatherError::RetriesExhausted {
+            attempts,
+            last_error,
+        } => {
+            assert_eq!(*attempts, 3);
+            assert!(
+                matches!(last_error.as_ref(), AmbientWeatherError::Http(err) if err.status().map(|status| status.as_u16()) == Some(500))
+            );
+        }
+        err => panic!("expected exhausted retries, got {err:?}"),
+    }
+    assert_eq!(server.paths().len(), 3);
+}
+
+#[tokio::test]
+async fn returns_errors_not_worth_retrying_as_they_are() {
+    let server = StandIn::start(|_, served| match served {
+        0 => Reply::status(503),
+        _ => Reply::status(401),
+    })
+    .await;
+
+    let err = server
+        .client(DeviceSelector::Index(0), quick_retries(5))
+        .list_devices()
+        .await
+        .unwrap_err();
+
+    assert!(matches!(err, AmbientWeatherError::Unauthorized), "{err:?}");
+    assert_eq!(server.paths().len(), 2);
+}
+
+#[tokio::test]
+async fn caps_retry_after_at_the_longest_backoff() {
+    let server = StandIn::start(|_, served| match served {
+        0 => Reply::status(429).header("retry-after", "3600"),
+        _ => Reply::json("[]"),
+    })
+    .await;
+    let client = server.client(DeviceSelector::Index(0), quick_retries(2));
+
+    let started = Instant::now();
+    client.list_devices().await.unwrap();
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(server.paths().len(), 2);
+}
+
+#[tokio::test]
+async fn reads_retry_after_in_seconds_and_as_a_date() {
+    let in_two_minutes = httpdate::fmt_http_date(SystemTime::now() + Duration::from_secs(120));
+    let server = StandIn::start(move |_, served| match served {
+        0 => Reply::status(429).header("retry-after", "7"),
+        1 => Reply::status(429).header("retry-after", in_two_minutes.clone()),
+        2 => Reply::status(429).header("retry-after", "Thu, 01 Jan 1970 00:00:00 GMT"),
+        _ => Reply::status(429),
+    })
+    .await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    let mut waits = Vec::new();
+    for _ in 0..4 {
+        match client.list_devices().await {
+            Err(AmbientWeatherError::RateLimited { retry_after }) => waits.push(retry_after),
+            other => panic!("expected a rate limit, got {other:?}"),
+        }
+    }
+
+    assert_eq!(waits[0], Some(Duration::from_secs(7)));
+    let date_wait = waits[1].unwrap();
+    assert!(Duration::from_secs(100) < date_wait && date_wait <= Duration::from_secs(120));
+    assert_eq!(waits[2], Some(Duration::ZERO));
+    assert_eq!(waits[3], None);
+}
+
+/// The MAC address of the stand-in's only device.
+const MAC_ADDRESS: &str = "00:0E:C6:20:0F:7B";
+
+/// The time of the newest record of the stand-in's history.
+const NEWEST: i64 = 1_697_385_600_000;
+
+/// The time between two records of the stand-in's history.
+const INTERVAL: i64 = 5 * 60 * 1000;
+
+/// Returns the value of a query parameter of a request target.
+fn query_param(target: &str, name: &str) -> Option<String> {
+    target
+        .split_once('?')?
+        .1
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.to_string())
+}
+
+/// Answers like the Ambient Weather API for one device with `records` records, five minutes apart and newest first, including the record at `endDate` itself as the API does. `page_prefix` is put in front of every page of history.
+fn history(records: i64, page_prefix: &'static str) -> impl Fn(&str, usize) -> Reply {
+    move |target, _| {
+        let path = target.split('?').next().unwrap();
+        if path.trim_end_matches('/') == "/v1/devices" {
+            return Reply::json(format!(
+                r#"[{{ "macAddress": "{MAC_ADDRESS}", "info": {{ "name": "Backyard" }}, "lastData": {{ "dateutc": {NEWEST}, "tempf": 58.1 }} }}]"#
+            ));
+        }
+
+        // The API answers MAC addresses that are not on the account with no records.
+        if path != format!("/v1/devices/{MAC_ADDRESS}") {
+            return Reply::json("[]");
+        }
+
+        let end_date = query_param(target, "endDate").map_or(NEWEST, |end| end.parse().unwrap());
+        let limit = query_param(target, "limit").map_or(288, |limit| limit.parse().unwrap());
+        let page: Vec<String> = (0..records)
+            .map(|step| NEWEST - step * INTERVAL)
+            .filter(|&dateutc| dateutc <= end_date)
+            .take(limit)
+            .map(|dateutc| format!(r#"{{ "dateutc": {dateutc}, "tempf": 60 }}"#))
+            .collect();
+
+        Reply::json(format!("[{page_prefix}{}]", page.join(",")))
+    }
+}
+
+/// Returns the time of the record a number of steps back from the newest one.
+fn steps_back(steps: i64) -> SystemTime {
+    SystemTime::UNIX_EPOCH + Duration::from_millis((NEWEST - steps * INTERVAL) as u64)
+}
+
+fn times(records: &[WeatherData]) -> Vec<i64> {
+    records
+        .iter()
+        .map(|data| data.dateutc.unwrap().as_millis())
+        .collect()
+}
+
+#[tokio::test]
+async fn walks_back_through_history_to_the_start() {
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    let records = client
+        .historic_pages(
+            &DeviceSelector::Index(0),
+            HistoricQuery::new().limit(4),
+            steps_back(7),
+        )
+        .await
+        .unwrap()
+        .collect_all()
+        .await
+        .unwrap();
+
+    let expected: Vec<i64> = (0..8).map(|step| NEWEST - step * INTERVAL).collect();
+    assert_eq!(times(&records), expected);
+    assert!(records.is_complete());
+}
+
+#[tokio::test]
+async fn walks_past_pages_of_records_at_the_end_date() {
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    // With one record per page, every other page only repeats the record at its end date
+    let records = client
+        .historic_pages(
+            &DeviceSelector::Index(0),
+            HistoricQuery::new().limit(1),
+            steps_back(4),
+        )
+        .await
+        .unwrap()
+        .collect_all()
+        .await
+        .unwrap();
+
+    let expected: Vec<i64> = (0..5).map(|step| NEWEST - step * INTERVAL).collect();
+    assert_eq!(times(&records), expected);
+}
+
+#[tokio::test]
+async fn streams_pages() {
+    use futures_core::Stream;
+    use std::pin::Pin;
+
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+    let mut pages = client
+        .historic_pages(
+            &DeviceSelector::Index(0),
+            HistoricQuery::new().limit(4),
+            steps_back(7),
+        )
+        .await
+        .unwrap();
+    assert_eq!(pages.mac_address(), MAC_ADDRESS);
+
+    let mut sizes = Vec::new();
+    while let Some(page) = std::future::poll_fn(|cx| Pin::new(&mut pages).poll_next(cx)).await {
+        sizes.push(page.unwrap().len());
+    }
+
+    assert_eq!(sizes, [4, 3, 1]);
+    assert!(pages.next_page().await.is_none());
+}
+
+#[tokio::test]
+async fn numbers_undecodable_records_by_page() {
+    let server = StandIn::start(history(10, r#""not a record","#)).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    let records = client
+        .historic_pages(
+            &DeviceSelector::Index(0),
+            HistoricQuery::new().limit(4),
+            steps_back(7),
+        )
+        .await
+        .unwrap()
+        .collect_all()
+        .await
+        .unwrap();
+
+    assert_eq!(records.len(), 8);
+    let positions: Vec<(usize, usize)> = records
+        .errors
+        .iter()
+        .map(|error| (error.page, error.index))
+        .collect();
+    assert_eq!(positions, [(0, 0), (1, 0), (2, 0)]);
+}
+
+#[tokio::test]
+async fn looks_up_devices_selected_by_index_or_name() {
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    let latest = client
+        .latest(&DeviceSelector::name("Backyard"))
+        .await
+        .unwrap();
+    assert_eq!(latest.tempf, Some(58.1));
+
+    let records = client.historic(&DeviceSelector::Index(0)).await.unwrap();
+    assert_eq!(records.len(), 10);
+
+    let device_path = format!("/v1/devices/{MAC_ADDRESS}");
+    assert_eq!(
+        server.paths(),
+        ["/v1/devices/", "/v1/devices/", device_path.as_str()]
+    );
+}
+
+#[tokio::test]
+async fn skips_the_device_lookup_for_mac_addresses() {
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+    let device = DeviceSelector::mac_address(MAC_ADDRESS.to_ascii_lowercase());
+
+    let records = client
+        .historic_query(&device, &HistoricQuery::new().limit(3))
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 3);
+
+    let records = client
+        .historic_pages(&device, HistoricQuery::new().limit(4), steps_back(5))
+        .await
+        .unwrap()
+        .collect_all()
+        .await
+        .unwrap();
+    assert_eq!(records.len(), 6);
+
+    assert!(server
+        .paths()
+        .iter()
+        .all(|path| *path == format!("/v1/devices/{MAC_ADDRESS}")));
+}
+
+#[tokio::test]
+async fn takes_the_latest_data_of_mac_addresses_from_the_device_list() {
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    // The device's `lastData`, as for every other selector, rather than its newest historic record
+    let latest = client
+        .latest(&DeviceSelector::mac_address(
+            MAC_ADDRESS.to_ascii_lowercase(),
+        ))
+        .await
+        .unwrap();
+    assert_eq!(latest.tempf, Some(58.1));
+    assert_eq!(server.paths(), ["/v1/devices/"]);
+}
+
+#[tokio::test]
+async fn reports_mac_addresses_that_match_no_device() {
+    let server = StandIn::start(history(10, "")).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+    let unknown = DeviceSelector::mac_address("00:00:00:00:00:00");
+
+    match client.latest(&unknown).await {
+        Err(AmbientWeatherError::DeviceNotFound(missing)) => assert_eq!(missing, unknown),
+        other => panic!("expected no device, got {other:?}"),
+    }
+    match client.historic(&unknown).await {
+        Err(AmbientWeatherError::DeviceNotFound(missing)) => assert_eq!(missing, unknown),
+        other => panic!("expected no device, got {other:?}"),
+    }
+
+    // A known device can have no records before an end date
+    let before_any = client
+        .historic_query(
+            &DeviceSelector::mac_address(MAC_ADDRESS),
+            &HistoricQuery::new().end_date(steps_back(20)),
+        )
+        .await
+        .unwrap();
+    assert!(before_any.is_empty());
+}
+
+/// The stand-in's answer to every request, the device list of an account with two stations.
+fn account(_: &str, _: usize) -> Reply {
+    Reply::json(include_str!("fixtures/devices/account.json"))
+}
+
+#[tokio::test]
+async fn lists_devices_with_their_metadata() {
+    let server = StandIn::start(account).await;
+
+    let devices = server
+        .client(DeviceSelector::Index(0), RetryPolicy::none())
+        .list_devices()
+        .await
+        .unwrap();
+
+    assert_eq!(devices.len(), 2);
+    assert_eq!(devices[0].mac_address, "00:0E:C6:20:0F:7B");
+    assert_eq!(devices[0].name(), Some("Backyard"));
+    assert_eq!(devices[0].info.location.as_deref(), Some("Home"));
+    let location = devices[0].info.coords.as_ref().unwrap();
+    assert_eq!(
+        location.coords.map(|coords| (coords.lat, coords.lon)),
+        Some((40.7128, -74.0))
+    );
+    assert_eq!(location.elevation, Some(10.0));
+    assert_eq!(devices[0].last_data.tempf, Some(58.1));
+    assert_eq!(devices[1].name(), Some("Cabin"));
+    assert_eq!(server.paths(), ["/v1/devices/"]);
+}
+
+#[tokio::test]
+async fn reports_rejected_keys() {
+    for status in [401, 403] {
+        let server = StandIn::start(move |_, _| Reply::status(status)).await;
+
+        let err = server
+            .client(DeviceSelector::Index(0), quick_retries(3))
+            .list_devices()
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(err, AmbientWeatherError::Unauthorized),
+            "{status}: {err:?}"
+        );
+        assert_eq!(server.paths().len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn reports_rate_limits() {
+    let server = StandIn::start(|_, _| Reply::status(429).header("retry-after", "2")).await;
+
+    let err = server
+        .client(DeviceSelector::Index(0), RetryPolicy::none())
+        .latest(&DeviceSelector::Index(0))
+        .await
+        .unwrap_err();
+
+    assert!(
+        matches!(err, AmbientWeatherError::RateLimited { retry_after: Some(wait) } if wait == Duration::from_secs(2)),
+        "{err:?}"
+    );
+}
+
+#[tokio::test]
+async fn reports_malformed_bodies() {
+    let bodies = [
+        ("not json", None),
+        (r#"{ "devices": [] }"#, None),
+        (r#"[{ "info": { "name": "No MAC address" } }]"#, None),
+        (
+            r#"[{ "macAddress": "00:0E:C6:20:0F:7B", "lastData": { "tempf": "warm" } }]"#,
+            Some("lastData.tempf"),
+        ),
+    ];
+
+    for (body, expected_field) in bodies {
+        let server = StandIn::start(move |_, _| Reply::json(body)).await;
+        let client = server
+            .builder(DeviceSelector::Index(0))
+            .decode_mode(DecodeMode::Strict)
+            .build()
+            .unwrap();
+
+        match client.list_devices().await {
+            Err(AmbientWeatherError::Decode { field, .. }) => {
+                assert_eq!(field.as_deref(), expected_field, "{body}")
+            }
+            other => panic!("expected a decode error for {body}, got {other:?}"),
+        }
+    }
+}
+
+#[tokio::test]
+async fn selects_devices_by_index_mac_address_or_name() {
+    let server = StandIn::start(account).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    let selectors = [
+        (DeviceSelector::Index(1), "C4:5B:BE:6D:21:90"),
+        (
+            DeviceSelector::mac_address("c4:5b:be:6d:21:90"),
+            "C4:5B:BE:6D:21:90",
+        ),
+        (DeviceSelector::name("Backyard"), "00:0E:C6:20:0F:7B"),
+    ];
+    for (selector, mac_address) in selectors {
+        let device = client.device(&selector).await.unwrap();
+        assert_eq!(device.mac_address, mac_address, "{selector}");
+    }
+}
+
+#[tokio::test]
+async fn reports_selectors_that_match_no_device() {
+    let server = StandIn::start(account).await;
+    let client = server.client(DeviceSelector::Index(0), RetryPolicy::none());
+
+    for selector in [
+        DeviceSelector::Index(2),
+        DeviceSelector::name("backyard"),
+        DeviceSelector::mac_address("00:00:00:00:00:00"),
+    ] {
+        match client.device(&selector).await {
+            Err(AmbientWeatherError::DeviceNotFound(missing)) => assert_eq!(missing, selector),
+            other => panic!("expected no device for {selector}, got {other:?}"),
+        }
+    }
+
+    let err = client
+        .latest(&DeviceSelector::name("Garage"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.to_string(), "no device found matching name \"Garage\"");
+}
@@ -0,0 +1,384 @@
+//! Tests for the realtime client against a local stand-in for the Ambient Weather Socket.IO server, speaking Engine.IO 3 over long-polling.
+
+use ambient_weather_api::*;
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    sync::Notify,
+};
+
+/// The shared state of the stand-in server.
+struct StandIn {
+    /// Packets waiting to be handed out by the next poll.
+    outbox: Mutex<VecDeque<String>>,
+    /// Wakes up polls waiting for the outbox.
+    ready: Notify,
+    /// Every message the client has posted.
+    received: Mutex<Vec<String>>,
+    /// How many sessions have been opened.
+    handshakes: AtomicUsize,
+    /// The ping interval and timeout announced in the handshake, in milliseconds.
+    ping_interval: AtomicU64,
+    /// Whether pings from the client are answered with pongs.
+    answer_pings: AtomicBool,
+    /// Whether posts from the client are answered at all, rather than left hanging.
+    answer_posts: AtomicBool,
+}
+
+impl Default for StandIn {
+    fn default() -> Self {
+        StandIn {
+            outbox: Mutex::default(),
+            ready: Notify::new(),
+            received: Mutex::default(),
+            handshakes: AtomicUsize::new(0),
+            ping_interval: AtomicU64::new(25000),
+            answer_pings: AtomicBool::new(true),
+            answer_posts: AtomicBool::new(true),
+        }
+    }
+}
+
+impl StandIn {
+    fn push(&self, packet: &str) {
+        self.outbox.lock().unwrap().push_back(packet.to_string());
+        self.ready.notify_waiters();
+    }
+}
+
+/// A parsed HTTP request.
+struct Request {
+    method: String,
+    target: String,
+    body: String,
+}
+
+async fn read_request(socket: &mut TcpStream) -> Option<Request> {
+    let mut buffer = Vec::new();
+    let mut chunk = [0u8; 4096];
+
+    let header_end = loop {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+        if let Some(position) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break position + 4;
+        }
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..header_end]).to_string();
+    let content_length = head
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.trim().parse::<usize>().ok())
+        .unwrap_or(0);
+
+    while buffer.len() < header_end + content_length {
+        let read = socket.read(&mut chunk).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    }
+
+    let mut request_line = head.lines().next()?.split(' ');
+
+    Some(Request {
+        method: request_line.next()?.to_string(),
+        target: request_line.next()?.to_string(),
+        body: String::from_utf8_lossy(&buffer[header_end..header_end + content_length]).to_string(),
+    })
+}
+
+fn frame(packets: &[String]) -> String {
+    packets
+        .iter()
+        .map(|packet| format!("{}:{packet}", packet.encode_utf16().count()))
+        .collect()
+}
+
+async fn respond(socket: &mut TcpStream, body: &str) {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\ncontent-type: text/plain; charset=UTF-8\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = socket.write_all(response.as_bytes()).await;
+}
+
+async fn handle(mut socket: TcpStream, state: Arc<StandIn>) {
+    let Some(request) = read_request(&mut socket).await else {
+        return;
+    };
+
+    if !request.target.contains("sid=") {
+        assert!(request.target.starts_with("/socket.io/"));
+        assert!(request.target.contains("applicationKey=test-app-key"));
+        assert!(request.target.contains("EIO=3"));
+
+        let session = state.handshakes.fetch_add(1, Ordering::SeqCst);
+        let ping_interval = state.ping_interval.load(Ordering::SeqCst);
+        let open = format!(
+            r#"0{{"sid":"stand-in-{session}","upgrades":[],"pingInterval":{ping_interval},"pingTimeout":{ping_interval}}}"#
+        );
+        respond(&mut socket, &frame(&[open, "40".to_string()])).await;
+    } else if request.method == "POST" {
+        state.received.lock().unwrap().push(request.body.clone());
+
+        if !state.answer_posts.load(Ordering::SeqCst) {
+            return std::future::pending().await;
+        }
+
+        if request.body.contains(r#"42["subscribe""#) {
+            state.push(
+                r#"42["subscribed",{"method":"subscribe","devices":[{"macAddress":"00:0E:C6:20:0F:7B","info":{"name":"Backyard"},"lastData":{"tempf":66.9}}]}]"#,
+            );
+            state.push(
+                r#"42["data",{"macAddress":"00:0E:C6:20:0F:7B","dateutc":1515436500000,"tempf":67.1,"humidity":45}]"#,
+            );
+        }
+
+        if request.body == "1:2" && state.answer_pings.load(Ordering::SeqCst) {
+            state.push("3");
+        }
+
+        respond(&mut socket, "ok").await;
+    } else {
+        let packets = loop {
+            let ready = state.ready.notified();
+            let packets: Vec<String> = state.outbox.lock().unwrap().drain(..).collect();
+            if !packets.is_empty() {
+                break packets;
+            }
+            ready.await;
+        };
+
+        respond(&mut socket, &frame(&packets)).await;
+    }
+}
+
+async fn start_stand_in() -> (String, Arc<StandIn>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let address = listener.local_addr().unwrap();
+    let state = Arc::new(StandIn::default());
+
+    let server_state = state.clone();
+    tokio::spawn(async move {
+        while let Ok((socket, _)) = listener.accept().await {
+            tokio::spawn(handle(socket, server_state.clone()));
+        }
+    });
+
+    (format!("http://{address}"), state)
+}
+
+#[tokio::test]
+async fn subscribes_and_receives_typed_events() {
+    let (base_url, state) = start_stand_in().await;
+
+    let client = RealtimeClient::builder("test-app-key")
+        .base_url(base_url)
+        .build()
+        .unwrap();
+    let mut subscription = client.subscribe(["key-one", "key-two"]).await.unwrap();
+
+    match subscription.next_event().await {
+        Some(Ok(RealtimeEvent::Subscribed(subscribed))) => {
+            assert_eq!(subscribed.devices.len(), 1);
+            assert_eq!(subscribed.devices[0].name(), Some("Backyard"));
+        }
+        other => panic!("expected a subscribed event, got {other:?}"),
+    }
+
+    match subscription.next_event().await {
+        Some(Ok(RealtimeEvent::Data(update))) => {
+            assert_eq!(update.mac_address, "00:0E:C6:20:0F:7B");
+            assert_eq!(update.data.tempf, Some(67.1));
+            assert_eq!(update.data.humidity, Some(45));
+        }
+        other => panic!("expected a data event, got {other:?}"),
+    }
+
+    let received = state.received.lock().unwrap();
+    assert!(received
+        .iter()
+        .any(|body| body.contains(r#"{"apiKeys":["key-one","key-two"]}"#)));
+}
+
+#[tokio::test]
+async fn reconnects_and_resubscribes_after_the_session_is_closed() {
+    let (base_url, state) = start_stand_in().await;
+
+    let client = RealtimeClient::builder("test-app-key")
+        .base_url(base_url)
+        .reconnect_policy(ReconnectPolicy {
+            initial_delay: Duration::from_millis(10),
+            ..ReconnectPolicy::default()
+        })
+        .build()
+        .unwrap();
+    let mut subscription = client.subscribe(["key-one"]).await.unwrap();
+
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Subscribed(_)))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Data(_)))
+    ));
+
+    state.push("1");
+
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Connection(
+            ConnectionState::Disconnected { .. }
+        )))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Connection(
+            ConnectionState::Reconnecting { attempt: 1, .. }
+        )))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Connection(ConnectionState::Reconnected)))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Subscribed(_)))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Data(_)))
+    ));
+
+    assert_eq!(state.handshakes.load(Ordering::SeqCst), 2);
+    let subscribes = state
+        .received
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|body| body.contains(r#"{"apiKeys":["key-one"]}"#))
+        .count();
+    assert_eq!(subscribes, 2);
+}
+
+#[tokio::test]
+async fn keeps_the_session_alive_with_heartbeats_and_notices_when_they_stop() {
+    let (base_url, state) = start_stand_in().await;
+    state.ping_interval.store(50, Ordering::SeqCst);
+
+    let client = RealtimeClient::builder("test-app-key")
+        .base_url(base_url)
+        .reconnect_policy(ReconnectPolicy::never())
+        .build()
+        .unwrap();
+    let mut subscription = client.subscribe(["key-one"]).await.unwrap();
+
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Subscribed(_)))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Data(_)))
+    ));
+
+    // While pings are answered, the session stays up well past the heartbeat timeout.
+    let quiet = tokio::time::timeout(Duration::from_millis(400), subscription.next_event()).await;
+    assert!(quiet.is_err(), "unexpected event {quiet:?}");
+
+    state.answer_pings.store(false, Ordering::SeqCst);
+
+    match subscription.next_event().await {
+        Some(Ok(RealtimeEvent::Connection(ConnectionState::Disconnected { reason }))) => {
+            assert!(reason.contains("heartbeat"), "unexpected reason {reason}");
+        }
+        other => panic!("expected a disconnect, got {other:?}"),
+    }
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Err(AmbientWeatherError::Realtime(_)))
+    ));
+    assert!(subscription.next_event().await.is_none());
+}
+
+#[tokio::test]
+async fn gives_up_on_sends_the_server_never_answers() {
+    let (base_url, state) = start_stand_in().await;
+    state.ping_interval.store(50, Ordering::SeqCst);
+
+    let client = RealtimeClient::builder("test-app-key")
+        .base_url(base_url)
+        .reconnect_policy(ReconnectPolicy::never())
+        .build()
+        .unwrap();
+    let mut subscription = client.subscribe(["key-one"]).await.unwrap();
+
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Subscribed(_)))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Data(_)))
+    ));
+
+    // The next ping is accepted but never answered, which must not hold up the subscription forever.
+    state.answer_posts.store(false, Ordering::SeqCst);
+
+    let event = tokio::time::timeout(Duration::from_secs(5), subscription.next_event())
+        .await
+        .expect("the subscription hung on a send");
+    match event {
+        Some(Ok(RealtimeEvent::Connection(ConnectionState::Disconnected { reason }))) => {
+            assert!(reason.contains("sending"), "unexpected reason {reason}");
+        }
+        other => panic!("expected a disconnect, got {other:?}"),
+    }
+    match subscription.next_event().await {
+        Some(Err(AmbientWeatherError::Realtime(reason))) => assert!(reason.contains("sending")),
+        other => panic!("expected the disconnect error, got {other:?}"),
+    }
+    assert!(subscription.next_event().await.is_none());
+}
+
+#[tokio::test]
+async fn reports_when_the_feed_goes_silent() {
+    let (base_url, _state) = start_stand_in().await;
+
+    let client = RealtimeClient::builder("test-app-key")
+        .base_url(base_url)
+        .silence_timeout(Duration::from_millis(100))
+        .build()
+        .unwrap();
+    let mut subscription = client.subscribe(["key-one"]).await.unwrap();
+
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Subscribed(_)))
+    ));
+    assert!(matches!(
+        subscription.next_event().await,
+        Some(Ok(RealtimeEvent::Data(_)))
+    ));
+    match subscription.next_event().await {
+        Some(Ok(RealtimeEvent::Connection(ConnectionState::Silent { since }))) => {
+            assert!(since >= Duration::from_millis(100));
+        }
+        other => panic!("expected a silence report, got {other:?}"),
+    }
+}
@@ -0,0 +1,94 @@
+//! Tests that serializing decoded records and devices reproduces the JSON the Ambient Weather API sent, for every fixture in `tests/fixtures`.
+//!
+//! Each file in `tests/fixtures/records` is an array of records, as returned by the `/v1/devices/:macAddress` endpoint, and each file in `tests/fixtures/devices` is an array of devices, as returned by the `/v1/devices` endpoint. New fixtures are picked up without changing this file.
+
+use ambient_weather_api::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{fs, path::PathBuf};
+
+/// Returns the path and contents of every fixture in a directory of `tests/fixtures`.
+fn fixtures(directory: &str) -> Vec<(PathBuf, String)> {
+    let directory = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(directory);
+
+    let mut fixtures: Vec<(PathBuf, String)> = fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .map(|path| {
+            let contents = fs::read_to_string(&path).unwrap();
+            (path, contents)
+        })
+        .collect();
+    fixtures.sort();
+
+    assert!(
+        !fixtures.is_empty(),
+        "no fixtures in {}",
+        directory.display()
+    );
+    fixtures
+}
+
+/// Decodes every item of every fixture in a directory as `T`, and checks that serializing it gives back the same JSON.
+fn assert_round_trips<T: DeserializeOwned + Serialize>(directory: &str) {
+    for (path, contents) in fixtures(directory) {
+        let original: Vec<Value> = serde_json::from_str(&contents).unwrap();
+        let decoded: Vec<T> = serde_json::from_str(&contents).unwrap();
+
+        for (index, (original, decoded)) in original.iter().zip(&decoded).enumerate() {
+            let serialized = serde_json::to_string(decoded).unwrap();
+            let round_tripped: Value = serde_json::from_str(&serialized).unwrap();
+
+            assert_eq!(
+                &round_tripped,
+                original,
+                "item {index} of {} does not round-trip",
+                path.display()
+            );
+        }
+    }
+}
+
+#[test]
+fn records_round_trip() {
+    assert_round_trips::<WeatherData>("records");
+}
+
+#[test]
+fn devices_round_trip() {
+    assert_round_trips::<Device>("devices");
+}
+
+#[test]
+fn renamed_fields_decode_into_snake_case_fields() {
+    let contents = include_str!("fixtures/records/ws2902_history.json");
+    let records: Vec<WeatherData> = serde_json::from_str(contents).unwrap();
+    let data = &records[0];
+
+    assert_eq!(data.dew_point, Some(54.28));
+    assert_eq!(data.feels_like, Some(58.1));
+    assert_eq!(data.dew_point_in, Some(48.6));
+    assert_eq!(data.feels_like_in, Some(70.9));
+    assert_eq!(
+        data.last_rain.map(|last_rain| last_rain.to_string()),
+        Some(String::from("2023-10-15T15:55:00.000Z"))
+    );
+    assert!(data.unknown_fields().next().is_none());
+}
+
+#[test]
+fn whole_numbers_are_written_without_a_fraction() {
+    let data: WeatherData =
+        serde_json::from_str(r#"{ "tempf": 58, "windspeedmph": 0, "dewPoint": 54.4 }"#).unwrap();
+
+    assert_eq!(
+        serde_json::to_string(&data).unwrap(),
+        r#"{"windspeedmph":0,"tempf":58,"dewPoint":54.4}"#
+    );
+}